@@ -1,12 +1,16 @@
 use {
     anyhow::{Result, anyhow},
-    clap::Parser,
-    quixote::{Answers, Bank, Class},
+    clap::{Parser, Subcommand, ValueEnum},
+    quixote::{
+        Answers, Bank, Blanks, Class, Columns, GradeScale, Quiz, QuizOptions, Section, Severity,
+    },
     rayon::prelude::*,
     std::{
+        collections::BTreeMap,
         fmt::Write as _,
         fs::File,
         io::{BufWriter, Write},
+        ops::RangeInclusive,
         path::{Path, PathBuf},
     },
 };
@@ -30,7 +34,8 @@ Quizzes and tests in Markdown
 ---\
     ",
     version,
-    max_term_width = 80
+    max_term_width = 80,
+    args_conflicts_with_subcommands = true
 )]
 struct Cli {
     /// Debug
@@ -45,6 +50,63 @@ struct Cli {
     #[arg(short, value_name = "answers.json")]
     answers: Option<PathBuf>,
 
+    /// Grade report format
+    #[arg(long, value_name = "FORMAT", default_value = "markdown")]
+    format: Format,
+
+    /// Unanswered questions are graded as wrong (with a warning) or are errors (`wrong`, `error`)
+    #[arg(long, value_name = "POLICY", default_value = "wrong")]
+    blanks: Blanks,
+
+    /// Write a feedback file for each student (`alvin-anderson.md`, ...) to DIR
+    #[arg(long, value_name = "DIR")]
+    feedback: Option<PathBuf>,
+
+    /// Grade scale (`LABEL=MIN,...` or a JSON file; default: `A=90,B=80,C=70,D=60,F=0`)
+    #[arg(short, long, value_name = "SCALE", value_parser = parse_grades)]
+    grades: Option<GradeScale>,
+
+    /// Columns of completed quiz CSV files (`name=HEADER,version=HEADER,N=HEADER,...`)
+    #[arg(long, value_name = "MAP", value_parser = parse_columns)]
+    columns: Option<Columns>,
+
+    /// Number of questions (default: all)
+    #[arg(short = 'n', long, value_name = "N")]
+    count: Option<usize>,
+
+    /// Number of questions from source files ending with PATH (repeatable)
+    #[arg(long, value_name = "PATH=N", value_parser = parse_quota)]
+    quota: Vec<(PathBuf, usize)>,
+
+    /// Only include questions with TAG (repeatable; all must match)
+    #[arg(long, value_name = "TAG")]
+    tag: Vec<String>,
+
+    /// Exclude questions with TAG (repeatable)
+    #[arg(long, value_name = "TAG")]
+    exclude_tag: Vec<String>,
+
+    /// Only include questions with a difficulty in RANGE (`N`, `A..=B`, `A..B`, `A..`, `..=B`)
+    #[arg(long, value_name = "RANGE", value_parser = parse_difficulty)]
+    difficulty: Option<RangeInclusive<u8>>,
+
+    /// Group questions into sections (JSON file with each section's heading, instructions, and
+    /// source files and/or tags)
+    #[arg(long, value_name = "PATH")]
+    sections: Option<PathBuf>,
+
+    /// Number of versions (`quiz-A.md`, `quiz-B.md`, ...) with a combined answer key
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    versions: usize,
+
+    /// Quiz title
+    #[arg(long, value_name = "TITLE")]
+    title: Option<String>,
+
+    /// Random seed (reuse the seed from `answers.json` to regenerate a quiz)
+    #[arg(short, long, value_name = "SEED")]
+    seed: Option<u64>,
+
     /// Disable randomization
     #[arg(short = 'R', hide = true)]
     no_random: bool,
@@ -55,6 +117,30 @@ struct Cli {
 
     #[arg(value_name = "PATH/GLOB")]
     arguments: Vec<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check question bank(s) for problems
+    Lint {
+        #[arg(value_name = "PATH/GLOB")]
+        arguments: Vec<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Markdown report
+    Markdown,
+
+    /// CSV with each student's score, percent, grade, and points per question
+    Csv,
+
+    /// JSON with each student's results and the summary statistics
+    Json,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -71,15 +157,14 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Check question bank(s)
+    if let Some(Command::Lint { arguments }) = &cli.command {
+        return lint(arguments);
+    }
+
     // Grade quiz(zes)
     if let Some(json) = &cli.answers {
-        let answers = Answers::from(json)?;
-        for path in &cli.arguments {
-            let mut class = Class::from(path)?;
-            class.grade(&answers);
-            println!("{}", class.markdown());
-        }
-        return Ok(());
+        return grade(&cli, json);
     }
 
     // Must have `-q` option (or `-d`)...
@@ -120,7 +205,13 @@ fn main() -> Result<()> {
     let quizzes: Vec<_> = quizzes.into_iter().map(|x| x.unwrap()).collect();
 
     // Create question bank
-    let bank = Bank::new(&cli.arguments)?;
+    let mut bank = Bank::new(&cli.arguments)?;
+    if !cli.tag.is_empty() || !cli.exclude_tag.is_empty() || cli.difficulty.is_some() {
+        bank = bank.filter(&cli.tag, &cli.exclude_tag, cli.difficulty.as_ref());
+        if bank.is_empty() {
+            return Err(anyhow!("No questions match the filter!"));
+        }
+    }
     if cli.debug {
         #[cfg(unix)]
         Pager::with_pager("bat -pl rust").setup();
@@ -128,43 +219,260 @@ fn main() -> Result<()> {
         println!("{bank:#?}\n");
     }
 
+    // Load sections
+    let sections = match &cli.sections {
+        Some(path) => Section::load(path)?,
+        None => vec![],
+    };
+    let options = QuizOptions::default()
+        .shuffle(!cli.no_random)
+        .seed(cli.seed)
+        .count(cli.count)
+        .quotas(&cli.quota)
+        .sections(&sections);
+
     // Generate quiz(zes)
     for dir in &quizzes {
-        let quiz = bank.quiz(!cli.no_random);
-        let answers = quiz.answers();
-        if cli.debug {
-            println!("{quiz:#?}\n");
-            println!("{answers:#?}\n");
+        let mut versions = bank.quizzes(cli.versions, &options)?;
+        if let Some(title) = &cli.title {
+            versions = versions.into_iter().map(|x| x.titled(title)).collect();
+        }
+        write_quizzes(dir, &versions, cli.debug)?;
+    }
+
+    Ok(())
+}
+
+//--------------------------------------------------------------------------------------------------
+
+fn lint(arguments: &[PathBuf]) -> Result<()> {
+    let lints = Bank::lint(arguments)?;
+    for lint in &lints {
+        println!("{lint}");
+    }
+    let errors = lints
+        .iter()
+        .filter(|x| x.severity() == Severity::Error)
+        .count();
+    let warnings = lints.len() - errors;
+    if errors > 0 {
+        Err(anyhow!(format!(
+            "Found {errors} error(s) and {warnings} warning(s)!"
+        )))
+    } else {
+        if warnings > 0 {
+            println!("Found {warnings} warning(s)");
+        }
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+fn grade(cli: &Cli, json: &Path) -> Result<()> {
+    let answers = Answers::from(json)?;
+    let columns = cli.columns.clone().unwrap_or_default();
+    for path in &cli.arguments {
+        let mut class = if path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("csv"))
+        {
+            Class::from_csv(path, &columns)?
         } else {
+            Class::from(path)?
+        };
+
+        // Check responses
+        let problems = class.validate(&answers, cli.blanks);
+        for problem in &problems {
+            eprintln!("{}: {problem}", path.display());
+        }
+        let errors = problems
+            .iter()
+            .filter(|x| x.severity() == Severity::Error)
+            .count();
+        if errors > 0 {
+            return Err(anyhow!(format!(
+                "Found {errors} error(s) and {} warning(s) in `{}`!",
+                problems.len() - errors,
+                path.display(),
+            )));
+        }
+
+        if let Some(grades) = &cli.grades {
+            class.set_grades(grades.clone());
+        }
+        class.grade(&answers);
+        let feedback = match &cli.feedback {
+            Some(dir) => feedback_files(dir, class.feedback(&answers))?,
+            None => vec![],
+        };
+        match cli.format {
+            Format::Markdown => println!("{}", class.markdown()),
+            Format::Csv => print!("{}", class.csv()),
+            Format::Json => println!("{}", class.json()),
+        }
+
+        // Write feedback files
+        if let Some(dir) = &cli.feedback {
+            std::fs::create_dir_all(dir)?;
+            for (path, content) in &feedback {
+                write_file(path, content)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+//--------------------------------------------------------------------------------------------------
+
+fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Name each student's feedback file in `dir` after the slug of their name
+
+Returns an error if a name has no letters or digits or two names have the same slug, so that no
+feedback file overwrites another.
+*/
+fn feedback_files(
+    dir: &Path,
+    feedback: BTreeMap<String, String>,
+) -> Result<Vec<(PathBuf, String)>> {
+    let mut names: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for name in feedback.keys() {
+        names
+            .entry(slug(name))
+            .or_default()
+            .push(format!("`{name}`"));
+    }
+    let errors = names
+        .iter()
+        .filter_map(|(slug, names)| {
+            if slug.is_empty() {
+                Some(format!("{} (no letters or digits)", names.join(", ")))
+            } else if names.len() > 1 {
+                Some(format!("{} (`{slug}.md`)", names.join(" and ")))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        return Err(anyhow!(format!(
+            "Cannot name feedback files: {}!",
+            errors.join(", "),
+        )));
+    }
+    Ok(feedback
+        .into_iter()
+        .map(|(name, content)| (dir.join(format!("{}.md", slug(&name))), content))
+        .collect())
+}
+
+//--------------------------------------------------------------------------------------------------
+
+fn write_quizzes(dir: &Path, versions: &[Quiz], debug: bool) -> Result<()> {
+    if debug {
+        for quiz in versions {
+            println!("{quiz:#?}\n");
+            println!("{:#?}\n", quiz.answers());
+        }
+    } else {
+        let mut files = vec![];
+        for quiz in versions {
+            let suffix = quiz.version().map(|x| format!("-{x}")).unwrap_or_default();
             let answers = quiz.answers();
-            let files = [
-                ("quiz.md", &quiz.markdown()),
-                ("answers.md", answers.markdown().as_ref().unwrap()),
-                ("answers.json", &answers.json()),
-            ]
+            files.push((format!("quiz{suffix}.md"), quiz.markdown()));
+            files.push((
+                format!("answers{suffix}.md"),
+                answers.markdown().clone().unwrap(),
+            ));
+            if versions.len() == 1 {
+                files.push((String::from("answers.json"), answers.json()));
+            }
+        }
+        if versions.len() > 1 {
+            files.push((
+                String::from("answers.json"),
+                Answers::versions(versions).json(),
+            ));
+        }
+        let files = files
             .par_iter()
             .map(|(filename, content)| write_file(&dir.join(filename), content))
             .collect::<Vec<_>>();
-            if files.par_iter().any(Result::is_err) {
-                return Err(anyhow!(format!(
-                    "Failed to write all files:\n{}",
-                    files.iter().filter_map(|x| x.as_ref().err()).fold(
-                        String::new(),
-                        |mut s, x| {
-                            writeln!(s, "* {x}").unwrap();
-                            s
-                        }
-                    ),
-                )));
-            }
+        if files.par_iter().any(Result::is_err) {
+            return Err(anyhow!(format!(
+                "Failed to write all files:\n{}",
+                files
+                    .iter()
+                    .filter_map(|x| x.as_ref().err())
+                    .fold(String::new(), |mut s, x| {
+                        writeln!(s, "* {x}").unwrap();
+                        s
+                    }),
+            )));
         }
     }
-
     Ok(())
 }
 
 //--------------------------------------------------------------------------------------------------
 
+fn parse_quota(s: &str) -> Result<(PathBuf, usize)> {
+    let Some((path, n)) = s.rsplit_once('=') else {
+        return Err(anyhow!(format!("Expected `PATH=N`: `{s}`")));
+    };
+    Ok((PathBuf::from(path), n.parse()?))
+}
+
+//--------------------------------------------------------------------------------------------------
+
+fn parse_grades(s: &str) -> Result<GradeScale> {
+    let path = Path::new(s);
+    if path.is_file() {
+        GradeScale::from(path)
+    } else {
+        s.parse().map_err(|e: String| anyhow!(e))
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+fn parse_columns(s: &str) -> Result<Columns> {
+    s.parse().map_err(|e: String| anyhow!(e))
+}
+
+//--------------------------------------------------------------------------------------------------
+
+fn parse_difficulty(s: &str) -> Result<RangeInclusive<u8>> {
+    let Some((start, end)) = s.split_once("..") else {
+        let n = s.parse()?;
+        return Ok(n..=n);
+    };
+    let start = if start.is_empty() { 0 } else { start.parse()? };
+    let end = if let Some(end) = end.strip_prefix('=') {
+        end.parse()?
+    } else if end.is_empty() {
+        u8::MAX
+    } else {
+        end.parse::<u8>()?
+            .checked_sub(1)
+            .ok_or_else(|| anyhow!(format!("Empty range: `{s}`")))?
+    };
+    Ok(start..=end)
+}
+
+//--------------------------------------------------------------------------------------------------
+
 fn write_file(path: &Path, data: &str) -> Result<()> {
     let f = File::create(path)?;
     let mut f = BufWriter::new(f);
@@ -3,48 +3,58 @@ use {
     anyhow::{Result, anyhow},
     conv::ValueFrom,
     glob::glob,
+    jiff::Timestamp,
     pulldown_cmark as pd,
-    rand::seq::SliceRandom,
+    rand::{
+        SeedableRng,
+        seq::{IndexedRandom, SliceRandom},
+    },
+    rand_chacha::ChaCha8Rng,
     rayon::prelude::*,
-    serde::Deserialize,
+    regex::{Regex, RegexBuilder},
+    serde::{Deserialize, Serialize},
     std::{
-        collections::{BTreeMap, BTreeSet, HashSet},
-        fmt::Write,
+        collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+        fmt::{self, Write},
+        ops::RangeInclusive,
         path::{Path, PathBuf},
     },
     veg::Veg,
 };
 
+mod stats;
+
 //--------------------------------------------------------------------------------------------------
 
 /**
-Letter grade scale
+Answer key file format version written by this version of quixote
 */
-const LETTER_GRADES: [(f32, char); 5] = [
-    (90.0, 'A'),
-    (80.0, 'B'),
-    (70.0, 'C'),
-    (60.0, 'D'),
-    (0.0, 'F'),
-];
+const FORMAT: u32 = 1;
 
-//--------------------------------------------------------------------------------------------------
+/**
+Question metadata keys ([`QuestionMeta`])
+*/
+const META_KEYS: [&str; 8] = [
+    "id",
+    "tags",
+    "points",
+    "difficulty",
+    "ignore-case",
+    "normalize-whitespace",
+    "scoring",
+    "shuffle",
+];
 
 /**
-Get the letter grade for a percentage score
+Default letter grade scale ([`GradeScale::default()`])
 */
-fn letter_grade(pct: f32) -> char {
-    LETTER_GRADES
-        .iter()
-        .find_map(|(threshold, letter)| {
-            if pct >= *threshold {
-                Some(*letter)
-            } else {
-                None
-            }
-        })
-        .unwrap()
-}
+const LETTER_GRADES: [(f32, &str); 5] = [
+    (90.0, "A"),
+    (80.0, "B"),
+    (70.0, "C"),
+    (60.0, "D"),
+    (0.0, "F"),
+];
 
 //--------------------------------------------------------------------------------------------------
 
@@ -58,24 +68,51 @@ fn answer_counter() -> AlphaCounter {
 //--------------------------------------------------------------------------------------------------
 
 /**
-Calculate basic statistics
+Generate the markdown for a numbered question's content (top level blocks)
+*/
+fn numbered(n: usize, blocks: &[String]) -> String {
+    let pre = format!("{n}. ");
+    let sep = format!("\n\n{}", " ".repeat(pre.len()));
+    format!(
+        "{pre}{}",
+        blocks
+            .iter()
+            .map(|x| x.replace('\n', &sep[1..]))
+            .collect::<Vec<_>>()
+            .join(&sep),
+    )
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Remove the markers (`>`) from a block quote
+*/
+fn unquote(block: &str) -> String {
+    block
+        .lines()
+        .map(|x| {
+            let x = x.trim_start();
+            let x = x.strip_prefix('>').unwrap_or(x);
+            x.strip_prefix(' ').unwrap_or(x)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
 
-```text
-let (min, max, mean, sum, count) = stats(&[1.0, 2.0, ...]);
-```
+//--------------------------------------------------------------------------------------------------
+
+/**
+Generate the markdown for an explanation as a block quote indented by `indent` spaces
 */
-fn calc_stats(v: &[f32]) -> (f32, f32, f32, f32, usize) {
-    let mut min = v[0];
-    let mut max = v[0];
-    let mut sum = v[0];
-    for i in v.iter().skip(1) {
-        min = min.min(*i);
-        max = max.max(*i);
-        sum += i;
-    }
-    let count = v.len();
-    let mean = sum / f32::value_from(count).unwrap();
-    (min, max, mean, sum, count)
+fn quoted(indent: usize, explanation: &str) -> String {
+    let pre = format!("{}> ", " ".repeat(indent));
+    explanation.lines().fold(String::new(), |mut s, x| {
+        writeln!(s, "{}", format!("{pre}{x}").trim_end()).unwrap();
+        s
+    }) + "\n"
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -89,6 +126,61 @@ fn fmt_percent(pct: f32) -> String {
 
 //--------------------------------------------------------------------------------------------------
 
+/**
+Convert a score to a string with up to 2 decimal places
+*/
+fn fmt_score(score: f32) -> String {
+    let s = format!("{score:.2}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        String::from("0")
+    } else {
+        s.to_string()
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Convert an optional statistic to a string with 2 decimal places (empty if undefined)
+*/
+fn fmt_stat(stat: Option<f32>) -> String {
+    stat.map(|x| format!("{x:.2}")).unwrap_or_default()
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Get the time a quiz is generated: `SOURCE_DATE_EPOCH` (seconds since the Unix epoch) if set,
+otherwise the current time
+*/
+fn generated() -> Result<Timestamp> {
+    match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(s) => s
+            .trim()
+            .parse()
+            .ok()
+            .and_then(|x| Timestamp::from_second(x).ok())
+            .ok_or_else(|| anyhow!(format!("Invalid `SOURCE_DATE_EPOCH`: `{s}`"))),
+        Err(_) => Ok(Timestamp::now()),
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Get the line and column numbers (1-based, in characters) of a byte offset in a string
+*/
+fn location(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset.min(input.len())];
+    (
+        before.matches('\n').count() + 1,
+        before.rsplit('\n').next().unwrap().chars().count() + 1,
+    )
+}
+
+//--------------------------------------------------------------------------------------------------
+
 /**
 Question bank
 */
@@ -103,9 +195,135 @@ impl Bank {
 
     # Errors
 
-    Returns an error if input files globs resolve to zero files
+    Returns an error if input files globs resolve to zero files, files cannot be read, or any
+    questions are malformed ([`ParseErrors`])
     */
     pub fn new(input_files: &[PathBuf]) -> Result<Bank> {
+        let (bank, errors) = Bank::load(input_files)?;
+        if errors.is_empty() {
+            Ok(bank)
+        } else {
+            Err(ParseErrors { errors }.into())
+        }
+    }
+
+    /**
+    Check the question banks at one or more paths / globs for problems
+
+    Reports malformed questions (see [`Bank::new()`]) and problems in well-formed questions: no
+    correct answer, all answers correct, duplicate answers, duplicate match answers, True/False
+    questions with extra choices, missing question content, and duplicate questions or ids.
+
+    # Errors
+
+    Returns an error if input files globs resolve to zero files or files cannot be read
+    */
+    pub fn lint(input_files: &[PathBuf]) -> Result<Vec<Lint>> {
+        let (bank, errors) = Bank::load(input_files)?;
+        let mut lints = errors
+            .iter()
+            .map(|x| Lint::new(&x.path, x.line, x.column, Severity::Error, &x.message))
+            .collect::<Vec<_>>();
+        let mut questions: HashMap<String, &Question> = HashMap::new();
+        let mut ids: HashMap<&str, &Question> = HashMap::new();
+        for q in &bank.questions {
+            let mut problems = vec![];
+            if q.content.is_empty() {
+                problems.push((Severity::Error, String::from("Missing question content")));
+            }
+
+            // Answers
+            let mut answers = HashSet::new();
+            for x in q.answers.iter().filter(|_x| q.kind != QuestionType::Cloze) {
+                if !answers.insert(&x.content) {
+                    problems.push((
+                        Severity::Error,
+                        format!("Duplicate answer: `{}`", x.content),
+                    ));
+                }
+            }
+            if q.kind == QuestionType::Match {
+                let mut correct = HashSet::new();
+                for x in q.answers.iter().filter_map(|x| x.correct.as_ref()) {
+                    if !correct.insert(x) {
+                        problems.push((Severity::Error, format!("Duplicate match answer: `{x}`")));
+                    }
+                }
+            } else if q.kind == QuestionType::Choice {
+                let n_correct = q.answers.iter().filter(|x| x.is_correct).count();
+                if n_correct == 0 {
+                    problems.push((Severity::Error, String::from("No correct answer")));
+                } else if n_correct > 1 && n_correct == q.answers.len() {
+                    problems.push((
+                        Severity::Warning,
+                        String::from(
+                            "All answers are correct (only one should be bold if this is a \
+                            single answer question)",
+                        ),
+                    ));
+                }
+                if q.answers.len() > 2
+                    && ["True", "False"]
+                        .iter()
+                        .all(|tf| q.answers.iter().any(|x| x.content == *tf))
+                {
+                    problems.push((
+                        Severity::Error,
+                        String::from("True/False question has extra choices"),
+                    ));
+                }
+            }
+
+            // Duplicate questions
+            let key = q
+                .content
+                .iter()
+                .flat_map(|x| x.split_whitespace())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !key.is_empty() {
+                if let Some(other) = questions.get(&key) {
+                    problems.push((
+                        Severity::Error,
+                        format!(
+                            "Duplicate of the question at {}:{}",
+                            other.source.display(),
+                            other.line,
+                        ),
+                    ));
+                } else {
+                    questions.insert(key, q);
+                }
+            }
+            if let Some(id) = q.meta.id() {
+                if let Some(other) = ids.get(id) {
+                    problems.push((
+                        Severity::Error,
+                        format!(
+                            "Duplicate id `{id}` (also at {}:{})",
+                            other.source.display(),
+                            other.line,
+                        ),
+                    ));
+                } else {
+                    ids.insert(id, q);
+                }
+            }
+
+            lints.extend(
+                problems
+                    .into_iter()
+                    .map(|(severity, message)| Lint::new(&q.source, q.line, 1, severity, &message)),
+            );
+        }
+        lints.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+        Ok(lints)
+    }
+
+    /**
+    Load the well-formed questions and parse errors from one or more paths / globs
+    */
+    fn load(input_files: &[PathBuf]) -> Result<(Bank, Vec<ParseError>)> {
         // Glob out input files
         let input_files = input_files
             .par_iter()
@@ -144,654 +362,4226 @@ impl Bank {
         }
 
         // Read input files
-        let questions: Vec<Result<Vec<Question>>> = input_files
+        let files: Vec<Result<(Vec<Question>, Vec<ParseError>)>> = input_files
             .par_iter()
             .map(|x| {
                 if let Ok(input) = std::fs::read_to_string(x) {
-                    let mut input = input.trim().to_string();
-                    input.push_str(if input.ends_with("---") {
-                        "\n\n"
-                    } else {
-                        "\n\n---\n\n"
-                    });
-                    let mut depth = 0;
-                    let mut content = vec![];
-                    let mut questions = vec![];
-                    for (event, range) in
-                        pd::Parser::new_ext(&input, pd::Options::all()).into_offset_iter()
-                    {
-                        match event {
-                            pd::Event::Start(_) => {
-                                depth += 1;
-                            }
-                            pd::Event::End(_) => {
-                                depth -= 1;
-                                if depth == 0 {
-                                    content.push(input[range.clone()].trim().to_string());
-                                }
-                            }
-                            pd::Event::Rule => {
-                                questions.push(Question::new(&content));
-                                content = vec![];
-                            }
-                            _ => {}
-                        }
-                    }
-                    Ok(questions)
+                    Ok(Bank::parse(&input, x))
                 } else {
                     Err(anyhow!(format!("`{}`", x.display())))
                 }
             })
             .collect();
-        if questions.par_iter().any(Result::is_err) {
-            Err(anyhow!(format!(
+        if files.par_iter().any(Result::is_err) {
+            return Err(anyhow!(format!(
                 "Could not read files: {}!",
-                questions
+                files
                     .iter()
                     .filter_map(|x| x.as_ref().err())
                     .map(ToString::to_string)
                     .collect::<Vec<_>>()
                     .join(", "),
-            )))
-        } else {
-            Ok(Bank {
-                questions: questions
-                    .iter()
-                    .flatten()
-                    .flatten()
-                    .cloned()
-                    .collect::<Vec<_>>(),
-            })
+            )));
         }
-    }
 
-    /**
-    Generate a quiz
-    */
-    #[must_use]
-    pub fn quiz(&self, shuffle: bool) -> Quiz {
-        Quiz::new(&self.questions, shuffle)
+        let mut questions = vec![];
+        let mut errors = vec![];
+        for (file_questions, file_errors) in files.into_iter().flatten() {
+            questions.extend(file_questions);
+            errors.extend(file_errors);
+        }
+        Ok((Bank { questions }, errors))
     }
-}
-
-//--------------------------------------------------------------------------------------------------
-
-/**
-Quiz question
-*/
-#[derive(Clone, Debug)]
-pub struct Question {
-    content: Vec<String>,
-    answers: Vec<Answer>,
-}
 
-impl Question {
     /**
-    Create a new quiz question
+    Parse the questions in a source file
     */
-    fn new(content: &[String]) -> Question {
-        let mut content = content.to_vec();
-        let answer_content = content.pop().unwrap();
-        let mut answers = vec![];
+    fn parse(input: &str, source: &Path) -> (Vec<Question>, Vec<ParseError>) {
+        if input.trim().is_empty() {
+            return (vec![], vec![]);
+        }
+        let offset = input.len() - input.trim_start().len();
+        let mut text = input.trim().to_string();
+        text.push_str(if text.ends_with("---") {
+            "\n\n"
+        } else {
+            "\n\n---\n\n"
+        });
         let mut depth = 0;
-        for (event, range) in
-            pd::Parser::new_ext(&answer_content, pd::Options::all()).into_offset_iter()
-        {
+        let mut content = vec![];
+        let mut questions = vec![];
+        let mut errors = vec![];
+        for (event, range) in pd::Parser::new_ext(&text, pd::Options::all()).into_offset_iter() {
             match event {
                 pd::Event::Start(_) => {
                     depth += 1;
                 }
-                pd::Event::End(tag) => {
+                pd::Event::End(_) => {
                     depth -= 1;
-                    if depth == 1 && matches!(tag, pd::TagEnd::Item) {
-                        answers.push(Answer::new(answer_content[range.clone()].trim()));
+                    if depth == 0 {
+                        let block = &text[range.clone()];
+                        let start = offset + range.start + block.len() - block.trim_start().len();
+                        content.push((start, block.trim().to_string()));
+                    }
+                }
+                pd::Event::Rule => {
+                    let line =
+                        location(input, content.first().map_or(offset + range.start, |x| x.0)).0;
+                    match Question::new(&content, source, line, offset + range.start) {
+                        Ok(question) => questions.push(question),
+                        Err((at, message)) => {
+                            errors.push(ParseError::new(source, input, at, &message));
+                        }
                     }
+                    content = vec![];
                 }
                 _ => {}
             }
         }
-        if answers[0].content == "False" {
-            answers.reverse();
+        (questions, errors)
+    }
+
+    /**
+    Create a new question bank with the questions that have all of `tags`, none of
+    `exclude_tags`, and a difficulty rating in the `difficulty` range (if any)
+    */
+    #[must_use]
+    pub fn filter(
+        &self,
+        tags: &[String],
+        exclude_tags: &[String],
+        difficulty: Option<&RangeInclusive<u8>>,
+    ) -> Bank {
+        Bank {
+            questions: self
+                .questions
+                .iter()
+                .filter(|x| {
+                    tags.iter().all(|tag| x.meta.tags.contains(tag))
+                        && !exclude_tags.iter().any(|tag| x.meta.tags.contains(tag))
+                        && difficulty.is_none_or(|range| {
+                            x.meta.difficulty.is_some_and(|d| range.contains(&d))
+                        })
+                })
+                .cloned()
+                .collect(),
         }
-        Question { content, answers }
     }
-}
 
-//--------------------------------------------------------------------------------------------------
+    /**
+    Return the number of questions in the bank
+    */
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.questions.len()
+    }
 
-/**
-Quiz answer
-*/
-#[derive(Clone, Debug)]
-struct Answer {
-    content: String,
-    is_correct: bool,
-    correct: Option<String>,
-}
+    /**
+    Return true if the bank has no questions
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty()
+    }
 
-impl Answer {
     /**
-    Create a new quiz answer
+    Generate a quiz ([`QuizOptions`])
+
+    The generation time is `SOURCE_DATE_EPOCH` (seconds since the Unix epoch), if set, so that the
+    answer key can be regenerated byte for byte, or the current time.
+
+    # Errors
+
+    Returns an error if there are not enough questions to satisfy the count and quotas, a question
+    is not in any of the sections, or `SOURCE_DATE_EPOCH` is invalid
     */
-    fn new(content: &str) -> Answer {
-        if let Some(s) = content.strip_prefix("- ") {
-            let (content, correct) = s.split_once(" => ").unwrap();
-            Answer {
-                content: content.to_string(),
-                is_correct: true,
-                correct: Some(correct.to_string()),
+    pub fn quiz(&self, options: &QuizOptions) -> Result<Quiz> {
+        Ok(self.quizzes(1, options)?.remove(0))
+    }
+
+    /**
+    Generate multiple versions of a quiz
+
+    Like [`Bank::quiz()`], but each version (`A`, `B`, ...) has the same questions in a different
+    order; use [`Answers::versions()`] to create a combined answer key.
+    A single version is identical to the quiz generated by [`Bank::quiz()`] with the same seed.
+
+    # Errors
+
+    Returns an error if `versions` is zero, there are not enough questions to satisfy the count and
+    quotas, a question is not in any of the sections, or `SOURCE_DATE_EPOCH` is invalid
+    */
+    pub fn quizzes(&self, versions: usize, options: &QuizOptions) -> Result<Vec<Quiz>> {
+        if versions == 0 {
+            return Err(anyhow!("Number of versions must be at least 1!"));
+        }
+        let seed = options.seed.unwrap_or_else(rand::random);
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let questions = self.sample(options.count, &options.quotas, &mut rng)?;
+        if !options.sections.is_empty() {
+            let missing = questions
+                .iter()
+                .filter(|q| !options.sections.iter().any(|x| x.includes(q)))
+                .map(|q| format!("`{}:{}`", q.source.display(), q.line))
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                return Err(anyhow!(format!(
+                    "Questions not in any section: {}!",
+                    missing.join(", "),
+                )));
             }
-        } else if content.starts_with("* **") && content.ends_with("**") {
-            Answer {
-                content: content[4..(content.len() - 2)].trim().to_string(),
-                is_correct: true,
-                correct: None,
+        }
+        let generated = generated()?;
+        let mut c = answer_counter();
+        Ok((0..versions)
+            .map(|_| {
+                let version = if versions > 1 { c.next() } else { None };
+                Quiz::new(
+                    questions.clone(),
+                    options,
+                    seed,
+                    version,
+                    generated,
+                    &mut rng,
+                )
+            })
+            .collect())
+    }
+
+    /**
+    Select questions for a quiz, in bank order
+    */
+    fn sample(
+        &self,
+        count: Option<usize>,
+        quotas: &[(PathBuf, usize)],
+        rng: &mut ChaCha8Rng,
+    ) -> Result<Vec<Question>> {
+        // Partition questions by quota
+        let mut pools = vec![vec![]; quotas.len()];
+        let mut rest = vec![];
+        for (i, question) in self.questions.iter().enumerate() {
+            if let Some(j) = quotas
+                .iter()
+                .position(|(path, _)| question.source.ends_with(path))
+            {
+                pools[j].push(i);
+            } else {
+                rest.push(i);
             }
-        } else {
-            Answer {
-                content: content[2..content.len()].trim().to_string(),
-                is_correct: false,
-                correct: None,
+        }
+
+        // Draw questions for each quota
+        let mut errors = vec![];
+        let mut selected = vec![];
+        for ((path, n), pool) in quotas.iter().zip(&pools) {
+            if pool.len() < *n {
+                errors.push(format!("`{}` ({} of {n})", path.display(), pool.len()));
+            }
+            selected.extend(pool.choose_multiple(rng, *n));
+        }
+        if !errors.is_empty() {
+            return Err(anyhow!(format!(
+                "Not enough questions for quotas: {}!",
+                errors.join(", "),
+            )));
+        }
+
+        // Draw the remaining questions
+        if let Some(count) = count {
+            let quotas_total = selected.len();
+            let Some(n) = count.checked_sub(quotas_total) else {
+                return Err(anyhow!(format!(
+                    "Quotas ({quotas_total}) exceed the number of questions ({count})!"
+                )));
+            };
+            if rest.len() < n {
+                return Err(anyhow!(format!(
+                    "Not enough questions: {} of {n}!",
+                    rest.len(),
+                )));
             }
+            selected.extend(rest.choose_multiple(rng, n));
+        } else {
+            selected.extend(rest);
         }
+
+        selected.sort_unstable();
+        Ok(selected
+            .into_iter()
+            .map(|i| self.questions[i].clone())
+            .collect())
     }
 }
 
 //--------------------------------------------------------------------------------------------------
 
 /**
-Quiz
+Malformed question in a question bank source file
 */
-#[allow(clippy::type_complexity)]
-#[derive(Debug)]
-pub struct Quiz {
-    questions: Vec<(Question, Option<(String, Vec<String>)>)>,
+#[derive(Clone, Debug)]
+pub struct ParseError {
+    path: PathBuf,
+    line: usize,
+    column: usize,
+    message: String,
 }
 
-impl Quiz {
+impl ParseError {
     /**
-    Create a new quiz
+    Create a new parse error at a byte offset in the source file
     */
-    fn new(questions: &[Question], shuffle: bool) -> Quiz {
-        let mut questions = questions.to_vec();
+    fn new(path: &Path, input: &str, offset: usize, message: &str) -> ParseError {
+        let (line, column) = location(input, offset);
+        ParseError {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
 
-        if shuffle {
-            let mut rng = rand::rng();
+    /**
+    Get the source file path
+    */
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
 
-            // Randomize questions
-            questions.shuffle(&mut rng);
+    /**
+    Get the line number (1-based)
+    */
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
 
-            // Randomize answers
-            for x in &mut questions {
-                if !["True", "False"].contains(&x.answers[0].content.as_str()) {
-                    x.answers.shuffle(&mut rng);
-                }
-            }
-        }
+    /**
+    Get the column number (1-based, in characters)
+    */
+    #[must_use]
+    pub fn column(&self) -> usize {
+        self.column
+    }
 
-        let questions = questions
-            .into_iter()
-            .map(|x| {
-                if x.answers[0].correct.is_some() {
-                    // Match question
-                    let correct = x
-                        .answers
-                        .iter()
-                        .map(|x| x.correct.as_ref().unwrap().clone())
-                        .collect::<Vec<_>>();
-                    let mut answers = (0..correct.len()).collect::<Vec<_>>();
-                    let mut rng = rand::rng();
-                    answers.shuffle(&mut rng);
-                    let mut c = answer_counter();
-                    let answers_content = answers.iter().fold(String::new(), |mut s, x| {
-                        writeln!(s, "    - {}. {}", c.next().unwrap(), &correct[*x]).unwrap();
-                        s
+    /**
+    Get the explanation
+    */
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message,
+        )
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Malformed questions in a question bank
+
+Returned (via [`anyhow::Error`]) by [`Bank::new()`]; use
+[`downcast_ref()`](anyhow::Error::downcast_ref) to access each [`ParseError`].
+*/
+#[derive(Debug)]
+pub struct ParseErrors {
+    errors: Vec<ParseError>,
+}
+
+impl ParseErrors {
+    /**
+    Get the parse errors
+    */
+    #[must_use]
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+}
+
+impl fmt::Display for ParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Malformed questions:")?;
+        for error in &self.errors {
+            write!(f, "\n* {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseErrors {}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Problem severity in a [`Lint`] or [`Problem`]
+*/
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            },
+        )
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Problem in a question bank found by [`Bank::lint()`]
+*/
+#[derive(Clone, Debug)]
+pub struct Lint {
+    path: PathBuf,
+    line: usize,
+    column: usize,
+    severity: Severity,
+    message: String,
+}
+
+impl Lint {
+    /**
+    Create a new lint
+    */
+    fn new(path: &Path, line: usize, column: usize, severity: Severity, message: &str) -> Lint {
+        Lint {
+            path: path.to_path_buf(),
+            line,
+            column,
+            severity,
+            message: message.to_string(),
+        }
+    }
+
+    /**
+    Get the source file path
+    */
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /**
+    Get the line number (1-based)
+    */
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /**
+    Get the column number (1-based, in characters)
+    */
+    #[must_use]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /**
+    Get the severity
+    */
+    #[must_use]
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /**
+    Get the explanation
+    */
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.severity,
+            self.message,
+        )
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Question type
+*/
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuestionType {
+    /// Single answer, multiple answer, or True/False
+    Choice,
+
+    /// Match items to answers
+    Match,
+
+    /// Typed answer checked against accepted answers and/or patterns
+    ShortAnswer,
+
+    /// Number checked against the answer with an optional tolerance and unit
+    Numeric,
+
+    /// Put items in the correct order
+    Ordering,
+
+    /// Fill in one or more blanks (`{{answer}}`) in the question content
+    Cloze,
+
+    /// Written response graded manually with a rubric
+    Essay,
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Quiz question
+*/
+#[derive(Clone, Debug)]
+pub struct Question {
+    content: Vec<String>,
+    answers: Vec<Answer>,
+    kind: QuestionType,
+    source: PathBuf,
+    line: usize,
+    meta: QuestionMeta,
+    explanation: Option<String>,
+}
+
+impl Question {
+    /**
+    Create a new quiz question
+
+    Each `content` item is a top level block and its byte offset in the source file; `line` is the
+    line number where the question starts and `end` is the offset of the rule that ends it.
+    On error, returns the offset of the problem and an explanation.
+    */
+    fn new(
+        content: &[(usize, String)],
+        source: &Path,
+        line: usize,
+        end: usize,
+    ) -> std::result::Result<Question, (usize, String)> {
+        let mut content = content.to_vec();
+        let meta = Question::take_meta(&mut content)?;
+        let explanation = Question::take_explanation(&mut content);
+        if !content.last().is_some_and(|x| Question::is_list(&x.1)) {
+            // Cloze question
+            let mut answers = vec![];
+            for (at, block) in &content {
+                for (start, _end, spec) in Question::blanks(block) {
+                    answers.push(Answer::blank(spec).map_err(|e| (at + start, e))?);
+                }
+            }
+            if !answers.is_empty() {
+                if let Some(scoring) = meta.scoring {
+                    return Err((
+                        content[0].0,
+                        format!("Scoring `{scoring}` does not apply to this question"),
+                    ));
+                }
+                return Ok(Question {
+                    content: content.into_iter().map(|x| x.1).collect(),
+                    answers,
+                    kind: QuestionType::Cloze,
+                    source: source.to_path_buf(),
+                    line,
+                    meta,
+                    explanation,
+                });
+            }
+        }
+        let Some((at, answer_content)) = content.pop() else {
+            return Err((end, String::from("Empty question")));
+        };
+        let (mut answers, ordered) = Question::items(at, &answer_content)?;
+        let kind = if ordered {
+            if answers.len() < 2 {
+                return Err((
+                    at,
+                    String::from("Ordering questions must have at least 2 items"),
+                ));
+            }
+            QuestionType::Ordering
+        } else if answers[0].correct.is_some() {
+            QuestionType::Match
+        } else if answers.iter().all(|x| x.form == Some(Form::Number)) {
+            if answers.len() > 1 {
+                return Err((
+                    at,
+                    String::from("Numeric questions must have exactly one answer (`* # `)"),
+                ));
+            }
+            QuestionType::Numeric
+        } else if answers
+            .iter()
+            .all(|x| matches!(x.form, Some(Form::Text | Form::Pattern)))
+        {
+            QuestionType::ShortAnswer
+        } else if answers.iter().all(|x| x.form == Some(Form::Criterion)) {
+            QuestionType::Essay
+        } else if answers.iter().any(|x| x.form.is_some()) {
+            return Err((
+                at,
+                String::from(
+                    "Choices (`* `), short answers (`* = `, `* ~ `), numeric answers (`* # `), and \
+                    rubric criteria (`* + `) cannot be mixed",
+                ),
+            ));
+        } else {
+            QuestionType::Choice
+        };
+        if meta.shuffle.is_some() && !matches!(kind, QuestionType::Choice | QuestionType::Match) {
+            return Err((
+                at,
+                String::from("Metadata `shuffle` does not apply to this question"),
+            ));
+        }
+        if let Some(scoring) = meta.scoring {
+            let correct = answers.iter().filter(|x| x.is_correct).count();
+            if !scoring.applies(kind, correct) {
+                return Err((
+                    at,
+                    format!("Scoring `{scoring}` does not apply to this question"),
+                ));
+            }
+        }
+        if answers[0].content == "False" {
+            answers.reverse();
+        }
+        Ok(Question {
+            content: content.into_iter().map(|x| x.1).collect(),
+            answers,
+            kind,
+            source: source.to_path_buf(),
+            line,
+            meta,
+            explanation,
+        })
+    }
+
+    /**
+    Get the source file path
+    */
+    #[must_use]
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /**
+    Get the line number where the question starts in the source file
+    */
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /**
+    Get the question metadata
+    */
+    #[must_use]
+    pub fn meta(&self) -> &QuestionMeta {
+        &self.meta
+    }
+
+    /**
+    Get the question type
+    */
+    #[must_use]
+    pub fn kind(&self) -> QuestionType {
+        self.kind
+    }
+
+    /**
+    Get the explanation of the correct answer, if any
+    */
+    #[must_use]
+    pub fn explanation(&self) -> Option<&str> {
+        self.explanation.as_deref()
+    }
+
+    /**
+    Parse the answers list (last block) of a question
+
+    Returns the answers and whether the list is ordered.
+    On error, returns the offset of the problem and an explanation.
+    */
+    fn items(at: usize, block: &str) -> std::result::Result<(Vec<Answer>, bool), (usize, String)> {
+        let mut answers = vec![];
+        let mut depth = 0;
+        let mut ordered = false;
+        for (event, range) in pd::Parser::new_ext(block, pd::Options::all()).into_offset_iter() {
+            match event {
+                pd::Event::Start(tag) => {
+                    if depth == 0 {
+                        let pd::Tag::List(start) = tag else {
+                            return Err((
+                                at,
+                                String::from(
+                                    "Missing answers (the last block of a question must be a \
+                                list)",
+                                ),
+                            ));
+                        };
+                        ordered = start.is_some();
+                    }
+                    depth += 1;
+                }
+                pd::Event::End(tag) => {
+                    depth -= 1;
+                    if depth == 1 && matches!(tag, pd::TagEnd::Item) {
+                        let (item, explanation) = Answer::explanation(block[range.clone()].trim());
+                        let mut answer = if ordered {
+                            Answer::step(item)
+                        } else {
+                            Answer::new(item).map_err(|e| (at + range.start, e))?
+                        };
+                        answer.explanation = explanation;
+                        answers.push(answer);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok((answers, ordered))
+    }
+
+    /**
+    Return true if a top level block is a list
+    */
+    fn is_list(block: &str) -> bool {
+        matches!(
+            pd::Parser::new_ext(block, pd::Options::all()).next(),
+            Some(pd::Event::Start(pd::Tag::List(_))),
+        )
+    }
+
+    /**
+    Remove and parse the metadata (a leading HTML comment with only metadata lines), if any
+
+    On error, returns the offset of the comment and an explanation.
+    */
+    fn take_meta(
+        content: &mut Vec<(usize, String)>,
+    ) -> std::result::Result<QuestionMeta, (usize, String)> {
+        if content.first().is_some_and(|x| QuestionMeta::is_meta(&x.1)) {
+            let (at, comment) = content.remove(0);
+            QuestionMeta::new(&comment).map_err(|e| (at, e.to_string()))
+        } else {
+            Ok(QuestionMeta::default())
+        }
+    }
+
+    /**
+    Remove and return the explanation (a trailing block quote without cloze blanks), if any
+    */
+    fn take_explanation(content: &mut Vec<(usize, String)>) -> Option<String> {
+        if content.len() > 1
+            && content
+                .last()
+                .is_some_and(|x| Question::is_quote(&x.1) && Question::blanks(&x.1).is_empty())
+        {
+            content.pop().map(|x| unquote(&x.1))
+        } else {
+            None
+        }
+    }
+
+    /**
+    Return true if a top level block is a block quote
+    */
+    fn is_quote(block: &str) -> bool {
+        matches!(
+            pd::Parser::new_ext(block, pd::Options::all()).next(),
+            Some(pd::Event::Start(pd::Tag::BlockQuote(_))),
+        )
+    }
+
+    /**
+    Find the cloze blanks (`{{answer}}`) in a block
+
+    Returns the start and end offsets and the content of each blank.
+    */
+    fn blanks(block: &str) -> Vec<(usize, usize, &str)> {
+        let mut blanks = vec![];
+        let mut i = 0;
+        while let Some(start) = block[i..].find("{{").map(|x| i + x) {
+            let Some(end) = block[(start + 2)..].find("}}").map(|x| start + 2 + x + 2) else {
+                break;
+            };
+            blanks.push((start, end, &block[(start + 2)..(end - 2)]));
+            i = end;
+        }
+        blanks
+    }
+
+    /**
+    Replace the cloze blanks in the question content
+
+    The function receives the blank number (1-based) and answer.
+    */
+    fn fill(&self, mut f: impl FnMut(usize, &Answer) -> String) -> Vec<String> {
+        let mut n = 0;
+        self.content
+            .iter()
+            .map(|block| {
+                let mut r = String::new();
+                let mut i = 0;
+                for (start, end, _spec) in Question::blanks(block) {
+                    r.push_str(&block[i..start]);
+                    r.push_str(&f(n + 1, &self.answers[n]));
+                    n += 1;
+                    i = end;
+                }
+                r.push_str(&block[i..]);
+                r
+            })
+            .collect()
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Quiz question metadata
+
+Optionally specified via an HTML comment at the start of a question with one `key: value` per
+line:
+
+```text
+<!--
+id: addition-1
+tags: arithmetic, addition
+points: 2
+difficulty: 1
+-->
+```
+
+Short answer and cloze questions also accept `ignore-case` and `normalize-whitespace` (`true` or
+`false`; both default to `true`), choice, match, and ordering questions accept `scoring`
+([`Scoring`]), and choice and match questions accept `shuffle` (`true` (default) or `false`).
+A comment without any of these keys (such as `<!-- TODO: reword -->`) is question content; a
+comment with any of them is metadata, and any other line in it is an error.
+*/
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct QuestionMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    points: Option<f32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    difficulty: Option<u8>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ignore_case: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    normalize_whitespace: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scoring: Option<Scoring>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shuffle: Option<bool>,
+}
+
+impl QuestionMeta {
+    /**
+    Get the key of each line of an HTML comment (`None` if a line is not `key: value`)
+    */
+    fn keys(comment: &str) -> Vec<Option<&str>> {
+        comment
+            .trim_start_matches("<!--")
+            .trim_end_matches("-->")
+            .lines()
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(|x| x.split_once(':').map(|x| x.0.trim()))
+            .collect()
+    }
+
+    /**
+    Return true if a block is an HTML comment with any known metadata key
+
+    Such a comment is parsed as metadata, so a line with an unknown key or without a colon is an
+    error rather than silently making the comment question content.
+    */
+    fn is_meta(block: &str) -> bool {
+        block.starts_with("<!--")
+            && QuestionMeta::keys(block)
+                .iter()
+                .flatten()
+                .any(|key| META_KEYS.contains(key))
+    }
+
+    /**
+    Parse question metadata from an HTML comment
+    */
+    fn new(comment: &str) -> Result<QuestionMeta> {
+        let mut meta = QuestionMeta::default();
+        let comment = comment.trim_start_matches("<!--").trim_end_matches("-->");
+        for line in comment.lines().map(str::trim).filter(|x| !x.is_empty()) {
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(anyhow!(format!("Invalid metadata line: `{line}`")));
+            };
+            let value = value.trim();
+            match key.trim() {
+                "id" => meta.id = Some(value.to_string()),
+                "tags" => {
+                    meta.tags = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|x| !x.is_empty())
+                        .map(String::from)
+                        .collect();
+                }
+                "points" => {
+                    let points: f32 = value
+                        .parse()
+                        .map_err(|e| anyhow!(format!("Invalid metadata points `{value}`: {e}")))?;
+                    if !points.is_finite() || points <= 0.0 {
+                        return Err(anyhow!(format!(
+                            "Invalid metadata points `{value}`: must be a positive number"
+                        )));
+                    }
+                    meta.points = Some(points);
+                }
+                "difficulty" => {
+                    meta.difficulty = Some(value.parse().map_err(|e| {
+                        anyhow!(format!("Invalid metadata difficulty `{value}`: {e}"))
+                    })?);
+                }
+                "ignore-case" => {
+                    meta.ignore_case = Some(value.parse().map_err(|e| {
+                        anyhow!(format!("Invalid metadata ignore-case `{value}`: {e}"))
+                    })?);
+                }
+                "normalize-whitespace" => {
+                    meta.normalize_whitespace = Some(value.parse().map_err(|e| {
+                        anyhow!(format!(
+                            "Invalid metadata normalize-whitespace `{value}`: {e}"
+                        ))
+                    })?);
+                }
+                "scoring" => {
+                    meta.scoring = Some(value.parse().map_err(|e| {
+                        anyhow!(format!("Invalid metadata scoring `{value}`: {e}"))
+                    })?);
+                }
+                "shuffle" => {
+                    meta.shuffle = Some(value.parse().map_err(|e| {
+                        anyhow!(format!("Invalid metadata shuffle `{value}`: {e}"))
+                    })?);
+                }
+                key => return Err(anyhow!(format!("Unknown metadata key: `{key}`"))),
+            }
+        }
+        Ok(meta)
+    }
+
+    /**
+    Get the stable question identifier
+    */
+    #[must_use]
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /**
+    Get the topic tags
+    */
+    #[must_use]
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /**
+    Get the point value
+    */
+    #[must_use]
+    pub fn points(&self) -> Option<f32> {
+        self.points
+    }
+
+    /**
+    Get the difficulty rating
+    */
+    #[must_use]
+    pub fn difficulty(&self) -> Option<u8> {
+        self.difficulty
+    }
+
+    /**
+    Get whether short answers ignore case (default: `true`)
+    */
+    #[must_use]
+    pub fn ignore_case(&self) -> bool {
+        self.ignore_case.unwrap_or(true)
+    }
+
+    /**
+    Get whether short answers normalize whitespace (default: `true`)
+    */
+    #[must_use]
+    pub fn normalize_whitespace(&self) -> bool {
+        self.normalize_whitespace.unwrap_or(true)
+    }
+
+    /**
+    Get the scoring policy, if specified
+    */
+    #[must_use]
+    pub fn scoring(&self) -> Option<Scoring> {
+        self.scoring
+    }
+
+    /**
+    Get whether the choices (or match items) are shuffled (default: `true`)
+    */
+    #[must_use]
+    pub fn shuffle(&self) -> bool {
+        self.shuffle.unwrap_or(true)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Typed answer form
+*/
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Form {
+    /// Accepted answer
+    Text,
+
+    /// Regular expression that matches accepted answers
+    Pattern,
+
+    /// Numeric answer
+    Number,
+
+    /// Essay rubric criterion
+    Criterion,
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Quiz answer
+*/
+#[derive(Clone, Debug)]
+struct Answer {
+    content: String,
+    is_correct: bool,
+    correct: Option<String>,
+    form: Option<Form>,
+    explanation: Option<String>,
+    pinned: bool,
+}
+
+impl Answer {
+    /**
+    Create a new quiz answer
+
+    On error, returns an explanation.
+    */
+    fn new(content: &str) -> std::result::Result<Answer, String> {
+        if let Some(s) = content.strip_prefix("* ^ ") {
+            let mut answer = Answer::new(&format!("* {s}"))?;
+            if answer.form.is_some() {
+                return Err(String::from("Only choices can be pinned (`* ^ `)"));
+            }
+            answer.pinned = true;
+            Ok(answer)
+        } else if let Some(s) = content.strip_prefix("- ") {
+            let Some((content, correct)) = s.split_once(" => ") else {
+                return Err(String::from(
+                    "Match item is missing ` => ` (use `* ` for choices)",
+                ));
+            };
+            Ok(Answer {
+                content: content.to_string(),
+                is_correct: true,
+                correct: Some(correct.to_string()),
+                form: None,
+                explanation: None,
+                pinned: false,
+            })
+        } else if let Some(s) = content.strip_prefix("* = ") {
+            Ok(Answer {
+                content: s.trim().to_string(),
+                is_correct: true,
+                correct: None,
+                form: Some(Form::Text),
+                explanation: None,
+                pinned: false,
+            })
+        } else if let Some(s) = content.strip_prefix("* ~ ") {
+            let pattern = s.trim();
+            Accept::check(pattern)?;
+            Ok(Answer {
+                content: pattern.to_string(),
+                is_correct: true,
+                correct: None,
+                form: Some(Form::Pattern),
+                explanation: None,
+                pinned: false,
+            })
+        } else if let Some(s) = content.strip_prefix("* # ") {
+            Numeric::new(s)?;
+            Ok(Answer {
+                content: s.trim().to_string(),
+                is_correct: true,
+                correct: None,
+                form: Some(Form::Number),
+                explanation: None,
+                pinned: false,
+            })
+        } else if let Some(s) = content.strip_prefix("* + ") {
+            Criterion::new(s)?;
+            Ok(Answer {
+                content: s.trim().to_string(),
+                is_correct: true,
+                correct: None,
+                form: Some(Form::Criterion),
+                explanation: None,
+                pinned: false,
+            })
+        } else if let Some(s) = content
+            .strip_prefix("* **")
+            .and_then(|x| x.strip_suffix("**"))
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+        {
+            Ok(Answer {
+                content: s.to_string(),
+                is_correct: true,
+                correct: None,
+                form: None,
+                explanation: None,
+                pinned: false,
+            })
+        } else {
+            let s = content.get(2..).unwrap_or_default().trim();
+            if s.is_empty() {
+                return Err(String::from("Empty answer"));
+            }
+            Ok(Answer {
+                content: s.to_string(),
+                is_correct: false,
+                correct: None,
+                form: None,
+                explanation: None,
+                pinned: false,
+            })
+        }
+    }
+
+    /**
+    Create a cloze blank answer
+
+    The content is one or more accepted answers separated by `|`, or `~` followed by a pattern.
+    On error, returns an explanation.
+    */
+    fn blank(content: &str) -> std::result::Result<Answer, String> {
+        let content = content.trim();
+        let (content, form) = if let Some(pattern) = content.strip_prefix('~') {
+            let pattern = pattern.trim();
+            Accept::check(pattern)?;
+            (pattern, Form::Pattern)
+        } else {
+            (content, Form::Text)
+        };
+        if content.is_empty() {
+            return Err(String::from("Empty blank"));
+        }
+        Ok(Answer {
+            content: content.to_string(),
+            is_correct: true,
+            correct: None,
+            form: Some(form),
+            explanation: None,
+            pinned: false,
+        })
+    }
+
+    /**
+    Split a list item into its content and explanation (a nested block quote), if any
+    */
+    fn explanation(item: &str) -> (&str, Option<String>) {
+        let mut start = 0;
+        for line in item.split_inclusive('\n') {
+            if start > 0 && line.trim_start().starts_with('>') {
+                return (item[..start].trim_end(), Some(unquote(&item[start..])));
+            }
+            start += line.len();
+        }
+        (item, None)
+    }
+
+    /**
+    Generate the markdown for the explanation (if any) under the answer in `answers.md`
+    */
+    fn explained(&self) -> String {
+        self.explanation
+            .as_deref()
+            .map(|x| quoted(8, x))
+            .unwrap_or_default()
+    }
+
+    /**
+    Create an ordering question item from an ordered list item
+    */
+    fn step(content: &str) -> Answer {
+        let content = content.trim_start_matches(|c: char| c.is_ascii_digit());
+        Answer {
+            content: content
+                .strip_prefix(['.', ')'])
+                .unwrap_or(content)
+                .trim()
+                .to_string(),
+            is_correct: true,
+            correct: None,
+            form: None,
+            explanation: None,
+            pinned: false,
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Quiz section
+
+A section has a heading, optional instructions, and includes the questions from the source files
+whose path ends with any of `files` and/or the questions with any of `tags`, or all questions if
+neither is given.
+A sections file is a JSON array of sections:
+
+```json
+[
+  {
+    "heading": "Part I: Multiple choice",
+    "instructions": "Select the best answer.",
+    "files": ["addition.md", "subtraction.md"]
+  },
+  {"heading": "Part II: Matching", "tags": ["vocabulary"]},
+  {"heading": "Part III: Other"}
+]
+```
+*/
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Section {
+    heading: String,
+
+    #[serde(default)]
+    instructions: Option<String>,
+
+    #[serde(default)]
+    files: Vec<PathBuf>,
+
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl Section {
+    /**
+    Load sections from a JSON file
+
+    # Errors
+
+    Returns an error if not able to read the file at the given path or deserialize it from JSON
+    */
+    pub fn load(path: &Path) -> Result<Vec<Section>> {
+        match serde_json::from_str(&std::fs::read_to_string(path)?) {
+            Ok(sections) => Ok(sections),
+            Err(e) => Err(anyhow!(format!(
+                "Could not parse {:?}: {e}",
+                path.display(),
+            ))),
+        }
+    }
+
+    /**
+    Get the heading
+    */
+    #[must_use]
+    pub fn heading(&self) -> &str {
+        &self.heading
+    }
+
+    /**
+    Get the instructions, if any
+    */
+    #[must_use]
+    pub fn instructions(&self) -> Option<&str> {
+        self.instructions.as_deref()
+    }
+
+    /**
+    Return true if the section includes a question
+    */
+    fn includes(&self, question: &Question) -> bool {
+        (self.files.is_empty() && self.tags.is_empty())
+            || self.files.iter().any(|x| question.source.ends_with(x))
+            || self.tags.iter().any(|x| question.meta.tags.contains(x))
+    }
+
+    /**
+    Generate the markdown for the heading and instructions
+    */
+    fn markdown(&self) -> String {
+        let mut s = format!("## {}\n\n", self.heading);
+        if let Some(instructions) = &self.instructions {
+            write!(s, "{}\n\n", instructions.trim()).unwrap();
+        }
+        s
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Options for generating a quiz ([`Bank::quiz()`]) or multiple versions of a quiz
+([`Bank::quizzes()`])
+
+All randomization draws from a single RNG seeded with the seed, or with a random seed if `None`.
+The seed is recorded in the answer key so the quiz can be regenerated later.
+
+The quiz includes `count` questions drawn at random without replacement, or all questions if
+`None`.
+Each of the `quotas` draws an exact number of questions from the source files whose path ends
+with the given path; the remaining questions are drawn from the other source files.
+If there are any `sections`, the questions are grouped into them ([`Section`]).
+
+```
+use quixote::QuizOptions;
+
+let options = QuizOptions::default().seed(Some(1234567890)).count(Some(10));
+```
+*/
+#[derive(Clone, Debug)]
+pub struct QuizOptions {
+    shuffle: bool,
+    seed: Option<u64>,
+    count: Option<usize>,
+    quotas: Vec<(PathBuf, usize)>,
+    sections: Vec<Section>,
+}
+
+impl QuizOptions {
+    /**
+    Set whether to shuffle the questions and answers (default: `true`)
+    */
+    #[must_use]
+    pub fn shuffle(mut self, shuffle: bool) -> QuizOptions {
+        self.shuffle = shuffle;
+        self
+    }
+
+    /**
+    Set the random seed (default: `None`, a random seed)
+    */
+    #[must_use]
+    pub fn seed(mut self, seed: Option<u64>) -> QuizOptions {
+        self.seed = seed;
+        self
+    }
+
+    /**
+    Set the number of questions (default: `None`, all questions)
+    */
+    #[must_use]
+    pub fn count(mut self, count: Option<usize>) -> QuizOptions {
+        self.count = count;
+        self
+    }
+
+    /**
+    Set the number of questions to draw from each source file path (default: none)
+    */
+    #[must_use]
+    pub fn quotas(mut self, quotas: &[(PathBuf, usize)]) -> QuizOptions {
+        self.quotas = quotas.to_vec();
+        self
+    }
+
+    /**
+    Set the sections (default: none)
+    */
+    #[must_use]
+    pub fn sections(mut self, sections: &[Section]) -> QuizOptions {
+        self.sections = sections.to_vec();
+        self
+    }
+}
+
+impl Default for QuizOptions {
+    /**
+    Shuffle, random seed, all questions, no quotas, no sections
+    */
+    fn default() -> QuizOptions {
+        QuizOptions {
+            shuffle: true,
+            seed: None,
+            count: None,
+            quotas: vec![],
+            sections: vec![],
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Quiz
+*/
+#[allow(clippy::type_complexity)]
+#[derive(Debug)]
+pub struct Quiz {
+    questions: Vec<(Question, Option<(String, Vec<String>)>)>,
+    canonical: Vec<usize>,
+    seed: u64,
+    version: Option<String>,
+    title: Option<String>,
+    generated: Timestamp,
+    sections: Vec<(usize, Section)>,
+}
+
+impl Quiz {
+    /**
+    Create a new quiz
+    */
+    fn new(
+        questions: Vec<Question>,
+        options: &QuizOptions,
+        seed: u64,
+        version: Option<String>,
+        generated: Timestamp,
+        rng: &mut ChaCha8Rng,
+    ) -> Quiz {
+        let (mut questions, sections) =
+            Quiz::order(questions, &options.sections, options.shuffle, rng);
+
+        if options.shuffle {
+            // Randomize answers (except true/false, pinned answers, and unshuffled questions)
+            for (_n, x) in &mut questions {
+                if matches!(x.kind, QuestionType::Choice | QuestionType::Match)
+                    && x.meta.shuffle()
+                    && !["True", "False"].contains(&x.answers[0].content.as_str())
+                {
+                    let free = (0..x.answers.len())
+                        .filter(|i| !x.answers[*i].pinned)
+                        .collect::<Vec<_>>();
+                    let mut shuffled = free
+                        .iter()
+                        .map(|i| x.answers[*i].clone())
+                        .collect::<Vec<_>>();
+                    shuffled.shuffle(rng);
+                    for (i, answer) in free.into_iter().zip(shuffled) {
+                        x.answers[i] = answer;
+                    }
+                }
+            }
+        }
+
+        let (canonical, questions): (Vec<_>, Vec<_>) = questions.into_iter().unzip();
+        let questions = questions
+            .into_iter()
+            .map(|x| {
+                if x.kind == QuestionType::Ordering {
+                    // Ordering question
+                    let mut items = (0..x.answers.len()).collect::<Vec<_>>();
+                    items.shuffle(rng);
+                    let mut c = answer_counter();
+                    let letters = items.iter().map(|_| c.next().unwrap()).collect::<Vec<_>>();
+                    let items_content =
+                        items
+                            .iter()
+                            .enumerate()
+                            .fold(String::new(), |mut s, (i, x_i)| {
+                                writeln!(s, "    - {}. {}", letters[i], x.answers[*x_i].content)
+                                    .unwrap();
+                                s
+                            });
+                    let mut key = vec![String::new(); items.len()];
+                    for (i, x_i) in items.iter().enumerate() {
+                        key[*x_i].clone_from(&letters[i]);
+                    }
+                    (x, Some((items_content, key)))
+                } else if x.kind == QuestionType::Match {
+                    // Match question
+                    let correct = x
+                        .answers
+                        .iter()
+                        .map(|x| x.correct.as_ref().unwrap().clone())
+                        .collect::<Vec<_>>();
+                    let mut answers = (0..correct.len()).collect::<Vec<_>>();
+                    answers.shuffle(rng);
+                    let mut c = answer_counter();
+                    let answers_content = answers.iter().fold(String::new(), |mut s, x| {
+                        writeln!(s, "    - {}. {}", c.next().unwrap(), &correct[*x]).unwrap();
+                        s
                     });
                     let mut c = answer_counter();
-                    let answers_letters = (0..correct.len())
-                        .map(|_| c.next().unwrap())
-                        .collect::<Vec<_>>();
-                    let mut key = (0..answers.len())
-                        .map(|_| String::from("?"))
-                        .collect::<Vec<_>>();
-                    for (i, x) in answers.iter().enumerate() {
-                        key[*x].clone_from(&answers_letters[i]);
+                    let answers_letters = (0..correct.len())
+                        .map(|_| c.next().unwrap())
+                        .collect::<Vec<_>>();
+                    let mut key = (0..answers.len())
+                        .map(|_| String::from("?"))
+                        .collect::<Vec<_>>();
+                    for (i, x) in answers.iter().enumerate() {
+                        key[*x].clone_from(&answers_letters[i]);
+                    }
+                    (x, Some((answers_content, key)))
+                } else {
+                    (x, None)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Quiz {
+            questions,
+            canonical,
+            seed,
+            version,
+            title: None,
+            generated,
+            sections,
+        }
+    }
+
+    /**
+    Number the questions (in bank order) and group them into sections
+
+    Each question is in the first section that includes it, or a final group without a heading if
+    there are no sections.
+    If `shuffle` is true, the questions are shuffled within each section.
+    Returns the questions with their canonical numbers, and the index of the first question of
+    each non-empty section.
+    */
+    #[allow(clippy::type_complexity)]
+    fn order(
+        questions: Vec<Question>,
+        sections: &[Section],
+        shuffle: bool,
+        rng: &mut ChaCha8Rng,
+    ) -> (Vec<(usize, Question)>, Vec<(usize, Section)>) {
+        let mut groups = vec![vec![]; sections.len() + 1];
+        for (i, question) in questions.into_iter().enumerate() {
+            let j = sections
+                .iter()
+                .position(|x| x.includes(&question))
+                .unwrap_or(sections.len());
+            groups[j].push((i + 1, question));
+        }
+        let mut ordered = vec![];
+        let mut starts = vec![];
+        for (j, mut group) in groups.into_iter().enumerate() {
+            if group.is_empty() {
+                continue;
+            }
+            if let Some(section) = sections.get(j) {
+                starts.push((ordered.len(), section.clone()));
+            }
+            if shuffle {
+                group.shuffle(rng);
+            }
+            ordered.extend(group);
+        }
+        (ordered, starts)
+    }
+
+    /**
+    Set the quiz title
+    */
+    #[must_use]
+    pub fn titled(mut self, title: &str) -> Quiz {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /**
+    Get the seed used to randomize the quiz
+    */
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /**
+    Get the title, if any
+    */
+    #[must_use]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /**
+    Get the time the quiz was generated
+    */
+    #[must_use]
+    pub fn generated(&self) -> Timestamp {
+        self.generated
+    }
+
+    /**
+    Get the version name (`A`, `B`, ...) if this is one of multiple versions of a quiz
+    */
+    #[must_use]
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /**
+    Generate the heading markdown (the title and version, if any)
+    */
+    fn heading(&self) -> String {
+        let mut s = String::new();
+        if let Some(title) = &self.title {
+            write!(s, "# {title}\n\n").unwrap();
+        }
+        if let Some(version) = &self.version {
+            write!(s, "**Version {version}**\n\n").unwrap();
+        }
+        s
+    }
+
+    /**
+    Combine the heading, section headings, and markdown for each question
+    */
+    fn body(&self, questions: &[String]) -> String {
+        let mut s = self.heading();
+        let mut sections = self.sections.iter().peekable();
+        for (i, question) in questions.iter().enumerate() {
+            if let Some((_start, section)) = sections.next_if(|x| x.0 == i) {
+                s.push_str(&section.markdown());
+            }
+            s.push_str(question);
+        }
+        s
+    }
+
+    /**
+    Generate quiz markdown
+    */
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn markdown(&self) -> String {
+        self.body(
+            &self
+                .questions
+                .par_iter()
+                .enumerate()
+                .map(|(i, (q, c))| {
+                    if q.kind == QuestionType::Cloze {
+                        format!(
+                            "{}\n\n",
+                            numbered(i + 1, &q.fill(|n, _x| format!("_____ ({n})"))),
+                        )
+                    } else if let (QuestionType::Ordering, Some((content, answers))) = (q.kind, c) {
+                        format!(
+                            "{}\n\n{content}\n    Order: {}\n\n",
+                            numbered(i + 1, &q.content),
+                            vec!["_____"; answers.len()].join(" "),
+                        )
+                    } else if let Some((content, _answers)) = c {
+                        format!(
+                            "{}\n\n{content}\n{}\n",
+                            numbered(i + 1, &q.content),
+                            q.answers.iter().fold(String::new(), |mut s, x| {
+                                writeln!(s, "    * _____ {}", x.content).unwrap();
+                                s
+                            }),
+                        )
+                    } else if q.kind == QuestionType::Essay {
+                        format!(
+                            "{}\n\n    Answer:\n\n{}",
+                            numbered(i + 1, &q.content),
+                            format!("    {}\n\n", "_".repeat(60)).repeat(6),
+                        )
+                    } else if matches!(q.kind, QuestionType::ShortAnswer | QuestionType::Numeric) {
+                        let unit = if q.kind == QuestionType::Numeric {
+                            Numeric::new(&q.answers[0].content)
+                                .ok()
+                                .and_then(|x| x.unit)
+                                .map(|x| format!(" {x}"))
+                                .unwrap_or_default()
+                        } else {
+                            String::new()
+                        };
+                        format!(
+                            "{}\n\n    Answer: ______________________________{unit}\n\n",
+                            numbered(i + 1, &q.content),
+                        )
+                    } else {
+                        let mut c = answer_counter();
+                        format!(
+                            "{}\n\n{}",
+                            numbered(i + 1, &q.content),
+                            q.answers.iter().fold(String::new(), |mut s, x| {
+                                writeln!(s, "    * [ ] {}. {}\n", c.next().unwrap(), x.content)
+                                    .unwrap();
+                                s
+                            }),
+                        )
+                    }
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /**
+    Generate quiz [`Answers`]
+    */
+    #[must_use]
+    pub fn answers(&self) -> Answers {
+        Answers::new(self)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Quiz answer key
+
+This struct is used in two ways:
+
+1. Generated via [`Quiz::answers()`] during quiz generation in order to generate
+   content for the answer key (`answers.json`) and quiz with answers
+   (`answers.md`) files
+2. Loaded from a saved answer key (`answers.json`) for grading a quiz
+
+A combined answer key for multiple versions of a quiz ([`Answers::versions()`]) contains the
+answer key for each version.
+*/
+#[allow(clippy::struct_field_names)]
+#[derive(Debug)]
+pub struct Answers {
+    answers: BTreeMap<usize, Key>,
+    title: Option<String>,
+    generated: Option<Timestamp>,
+    seed: Option<u64>,
+    questions: BTreeMap<usize, KeyQuestion>,
+    versions: BTreeMap<String, Answers>,
+    markdown: Option<String>,
+}
+
+impl Answers {
+    /**
+    Create a new quiz answer key
+    */
+    fn new(quiz: &Quiz) -> Answers {
+        let answers: BTreeMap<usize, Key> = quiz
+            .questions
+            .par_iter()
+            .enumerate()
+            .map(|(i, (q, c))| (i + 1, Key::new(q, c.as_ref())))
+            .collect();
+
+        let questions_markdown = Answers::questions_markdown(quiz, &answers);
+
+        let questions = answers
+            .iter()
+            .map(|(n, key)| {
+                let (q, _c) = &quiz.questions[n - 1];
+                let mut meta = q.meta.clone();
+                meta.points = Some(meta.points.unwrap_or_else(|| key.points()));
+                (
+                    *n,
+                    KeyQuestion {
+                        kind: q.kind,
+                        source: q.source.clone(),
+                        canonical: quiz.version.as_ref().map(|_| quiz.canonical[n - 1]),
+                        options: (q.kind == QuestionType::Choice).then_some(q.answers.len()),
+                        meta,
+                        markdown: Some(questions_markdown[n - 1].clone()),
+                        explanation: q.explanation.clone(),
+                    },
+                )
+            })
+            .collect();
+
+        let markdown = Some(quiz.body(&questions_markdown));
+
+        Answers {
+            answers,
+            title: quiz.title.clone(),
+            generated: Some(quiz.generated),
+            seed: Some(quiz.seed),
+            questions,
+            versions: BTreeMap::new(),
+            markdown,
+        }
+    }
+
+    /**
+    Generate the quiz with answers markdown for each question
+    */
+    fn questions_markdown(quiz: &Quiz, answers: &BTreeMap<usize, Key>) -> Vec<String> {
+        quiz.questions
+            .par_iter()
+            .enumerate()
+            .map(|(i, (q, c))| {
+                let mut s = if q.kind == QuestionType::Cloze {
+                    format!("{}\n\n", numbered(i + 1, &q.fill(Answers::blank_markdown)))
+                } else if let (QuestionType::Ordering, Some((content, answers))) = (q.kind, c) {
+                    format!(
+                        "{}\n\n{content}\n    Order: **{}**\n\n",
+                        numbered(i + 1, &q.content),
+                        answers.join(" "),
+                    )
+                } else if let Some((content, answers)) = c {
+                    format!(
+                        "{}\n\n{content}\n{}",
+                        numbered(i + 1, &q.content),
+                        q.answers
+                            .iter()
+                            .enumerate()
+                            .fold(String::new(), |mut s, (i, x)| {
+                                writeln!(s, "    * {}: **{}**\n", x.content, &answers[i]).unwrap();
+                                s.push_str(&x.explained());
+                                s
+                            }),
+                    )
+                } else if q.kind == QuestionType::Essay {
+                    format!(
+                        "{}\n\n    Rubric:\n\n{}",
+                        numbered(i + 1, &q.content),
+                        q.answers
+                            .iter()
+                            .filter_map(|x| Criterion::new(&x.content).ok().map(|c| (x, c)))
+                            .fold(String::new(), |mut s, (x, c)| {
+                                writeln!(
+                                    s,
+                                    "    * {} (**{}** {})\n",
+                                    c.description,
+                                    c.points,
+                                    if c.points == 1 { "point" } else { "points" },
+                                )
+                                .unwrap();
+                                s.push_str(&x.explained());
+                                s
+                            }),
+                    )
+                } else if matches!(q.kind, QuestionType::ShortAnswer | QuestionType::Numeric) {
+                    format!(
+                        "{}\n\n{}",
+                        numbered(i + 1, &q.content),
+                        q.answers
+                            .iter()
+                            .map(|x| {
+                                if x.form == Some(Form::Pattern) {
+                                    format!("    * Pattern: `{}`\n\n{}", x.content, x.explained())
+                                } else {
+                                    format!("    * **{}**\n\n{}", x.content, x.explained())
+                                }
+                            })
+                            .collect::<String>(),
+                    )
+                } else {
+                    let mut c = answer_counter();
+                    let n = i + 1;
+                    let Some(Key::Choice(letters, _)) = answers.get(&n) else {
+                        unreachable!()
+                    };
+                    let ans: HashSet<_> = letters.iter().collect();
+                    format!(
+                        "{}\n\n{}",
+                        numbered(n, &q.content),
+                        q.answers
+                            .iter()
+                            .map(|x| {
+                                let letter = c.next().unwrap();
+                                if ans.contains(&letter) {
+                                    format!(
+                                        "    * [X] **{letter}. {}**\n\n{}",
+                                        x.content,
+                                        x.explained(),
+                                    )
+                                } else {
+                                    format!(
+                                        "    * [ ] {letter}. {}\n\n{}",
+                                        x.content,
+                                        x.explained()
+                                    )
+                                }
+                            })
+                            .collect::<String>(),
+                    )
+                };
+                if let Some(explanation) = &q.explanation {
+                    s.push_str(&quoted(4, &format!("**Explanation:** {explanation}")));
+                }
+                s
+            })
+            .collect()
+    }
+
+    /**
+    Generate the markdown for a filled in cloze blank
+    */
+    fn blank_markdown(n: usize, answer: &Answer) -> String {
+        if answer.form == Some(Form::Pattern) {
+            format!("`{}` ({n})", answer.content)
+        } else {
+            format!(
+                "{} ({n})",
+                answer
+                    .content
+                    .split('|')
+                    .map(|x| format!("**{}**", x.trim()))
+                    .collect::<Vec<_>>()
+                    .join(" / "),
+            )
+        }
+    }
+
+    /**
+    Generate a combined answer key for multiple versions of a quiz ([`Bank::quizzes()`])
+
+    Each version's answer key maps its question numbers back to the canonical question numbers.
+    */
+    #[must_use]
+    pub fn versions(quizzes: &[Quiz]) -> Answers {
+        Answers {
+            answers: BTreeMap::new(),
+            title: quizzes.first().and_then(|x| x.title.clone()),
+            generated: quizzes.first().map(|x| x.generated),
+            seed: quizzes.first().map(|x| x.seed),
+            questions: BTreeMap::new(),
+            versions: quizzes
+                .iter()
+                .map(|x| {
+                    let mut answers = x.answers();
+                    answers.title = None;
+                    answers.generated = None;
+                    answers.seed = None;
+                    (x.version.clone().unwrap_or_default(), answers)
+                })
+                .collect(),
+            markdown: None,
+        }
+    }
+
+    /**
+    Load from a JSON file
+
+    # Errors
+
+    Returns an error if not able to read the file at the given path and deserialize it from JSON
+    */
+    pub fn from(path: &Path) -> Result<Answers> {
+        let json = std::fs::read_to_string(path)?;
+        let key = if serde_json::from_str::<serde_json::Value>(&json)
+            .is_ok_and(|x| x.get("answers").is_some() || x.get("versions").is_some())
+        {
+            serde_json::from_str(&json)
+        } else {
+            // Bare answers map written by quixote 0.7 and earlier
+            serde_json::from_str(&json).map(|answers| AnswerKey {
+                answers,
+                ..AnswerKey::default()
+            })
+        };
+        match key {
+            Ok(key) if key.format.unwrap_or_default() > FORMAT => Err(anyhow!(format!(
+                "Could not read {:?}: answer key format {} is newer than the supported format \
+                {FORMAT} (please upgrade quixote)",
+                path.display(),
+                key.format.unwrap_or_default(),
+            ))),
+            Ok(key) => Ok(Answers::from_key(key)),
+            Err(e) => Err(anyhow!(format!(
+                "Could not parse {:?}: {e}",
+                path.display(),
+            ))),
+        }
+    }
+
+    /**
+    Create from the answer key file format
+    */
+    fn from_key(key: AnswerKey) -> Answers {
+        Answers {
+            answers: key.answers,
+            title: key.title,
+            generated: key.generated,
+            seed: key.seed,
+            questions: key.questions,
+            versions: key
+                .versions
+                .into_iter()
+                .map(|(version, key)| (version, Answers::from_key(key)))
+                .collect(),
+            markdown: None,
+        }
+    }
+
+    /**
+    Convert to the answer key file format
+    */
+    fn key(&self) -> AnswerKey {
+        AnswerKey {
+            format: None,
+            title: self.title.clone(),
+            generated: self.generated,
+            seed: self.seed,
+            questions: self.questions.clone(),
+            answers: self.answers.clone(),
+            versions: self
+                .versions
+                .iter()
+                .map(|(version, answers)| (version.clone(), answers.key()))
+                .collect(),
+        }
+    }
+
+    /**
+    Serialize to a JSON string
+
+    # Panics
+
+    Panics if not able to serialize to a JSON string
+    */
+    #[must_use]
+    pub fn json(&self) -> String {
+        serde_json::to_string(&AnswerKey {
+            format: Some(FORMAT),
+            ..self.key()
+        })
+        .unwrap()
+    }
+
+    /**
+    Get the quiz title, if recorded in the answer key
+    */
+    #[must_use]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /**
+    Get the time the quiz was generated, if recorded in the answer key
+    */
+    #[must_use]
+    pub fn generated(&self) -> Option<Timestamp> {
+        self.generated
+    }
+
+    /**
+    Get the seed used to generate the quiz, if recorded in the answer key
+    */
+    #[must_use]
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /**
+    Get the metadata for a particular question, if any
+    */
+    #[must_use]
+    pub fn meta(&self, question: usize) -> Option<&QuestionMeta> {
+        self.questions.get(&question).map(|x| &x.meta)
+    }
+
+    /**
+    Get the canonical question number for a particular question of a version
+    */
+    #[must_use]
+    pub fn canonical(&self, question: usize) -> Option<usize> {
+        self.questions.get(&question)?.canonical
+    }
+
+    /**
+    Get the answer key for a version, or this answer key if it does not have versions
+    */
+    fn version(&self, version: Option<&str>) -> Option<&Answers> {
+        if self.versions.is_empty() {
+            Some(self)
+        } else {
+            self.versions.get(version?)
+        }
+    }
+
+    /**
+    Calculate the total number of points in the quiz
+    */
+    fn total(&self) -> f32 {
+        if let Some(answers) = self.versions.values().next() {
+            answers.total()
+        } else {
+            self.answers.keys().filter_map(|x| self.points(*x)).sum()
+        }
+    }
+
+    /**
+    Return the number of questions on the quiz
+    */
+    fn questions(&self) -> usize {
+        if let Some(answers) = self.versions.values().next() {
+            answers.questions()
+        } else {
+            self.answers.len()
+        }
+    }
+
+    /**
+    Get the answers for a particular problem
+    */
+    fn get(&self, key: usize) -> Option<&Key> {
+        self.answers.get(&key)
+    }
+
+    /**
+    Get the point value for a particular question
+
+    Defaults to one point per correct letter, blank, item, or rubric point if not recorded in the
+    answer key.
+    */
+    fn points(&self, question: usize) -> Option<f32> {
+        self.questions
+            .get(&question)
+            .and_then(|x| x.meta.points)
+            .or_else(|| self.answers.get(&question).map(Key::points))
+    }
+
+    /**
+    Get the markdown for a particular question with its answers, if recorded in the answer key
+    */
+    fn question_markdown(&self, question: usize) -> Option<&str> {
+        self.questions.get(&question)?.markdown.as_deref()
+    }
+
+    /**
+    Get the explanation of a particular question's correct answer, if recorded in the answer key
+    */
+    #[must_use]
+    pub fn explanation(&self, question: usize) -> Option<&str> {
+        self.questions.get(&question)?.explanation.as_deref()
+    }
+
+    /**
+    Get the type of a particular question, if recorded in the answer key
+    */
+    #[must_use]
+    pub fn kind(&self, question: usize) -> Option<QuestionType> {
+        self.questions.get(&question).map(|x| x.kind)
+    }
+
+    /**
+    Get the source file and question id of a particular question, if recorded in the answer key
+    */
+    #[must_use]
+    pub fn source(&self, question: usize) -> Option<(&Path, Option<&str>)> {
+        self.questions
+            .get(&question)
+            .map(|x| (x.source.as_path(), x.meta.id()))
+    }
+
+    /**
+    Get the number of choices for a particular choice question, if recorded in the answer key
+    */
+    fn options(&self, question: usize) -> Option<usize> {
+        self.questions.get(&question)?.options
+    }
+
+    /**
+    Return the Markdown content
+    */
+    #[must_use]
+    pub fn markdown(&self) -> &Option<String> {
+        &self.markdown
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Answer key file format (`answers.json`)
+
+`format` is the file format version ([`FORMAT`]); answer keys without it were written by quixote
+0.7 and earlier.
+*/
+#[derive(Default, Deserialize, Serialize)]
+struct AnswerKey {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    format: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    generated: Option<Timestamp>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    questions: BTreeMap<usize, KeyQuestion>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    answers: BTreeMap<usize, Key>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    versions: BTreeMap<String, AnswerKey>,
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Answer key description of a question
+
+`canonical` is the question's number in the canonical order (for a version of a quiz), `options`
+is the number of choices of a choice question, and the metadata ([`QuestionMeta`]) always includes
+the point value.
+`markdown` is the question with its answers and explanations, as in `answers.md`, and
+`explanation` is the question's explanation of the correct answer.
+*/
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct KeyQuestion {
+    #[serde(rename = "type")]
+    kind: QuestionType,
+
+    source: PathBuf,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    canonical: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    options: Option<usize>,
+
+    #[serde(flatten)]
+    meta: QuestionMeta,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    markdown: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    explanation: Option<String>,
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Answer key for a question
+*/
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum Key {
+    /// Correct answer letters and whether this is a match question
+    Choice(Vec<String>, bool),
+
+    /// Accepted short answers
+    ShortAnswer(Accept),
+
+    /// Numeric answer
+    Numeric(Numeric),
+
+    /// Correct order of an ordering question's items
+    Ordering(Sequence),
+
+    /// Accepted answers for each blank of a cloze question
+    Cloze { blanks: Vec<Accept> },
+
+    /// Rubric for a manually graded essay question
+    Essay { rubric: Vec<Criterion> },
+}
+
+impl Key {
+    /**
+    Create the answer key for a question of a quiz
+
+    `c` is the shuffled content and correct letters of a match or ordering question.
+    */
+    fn new(q: &Question, c: Option<&(String, Vec<String>)>) -> Key {
+        if let (QuestionType::Ordering, Some((_content, answers))) = (q.kind, c) {
+            Key::Ordering(Sequence {
+                order: answers.clone(),
+                scoring: q.meta.scoring().unwrap_or(Scoring::PerPosition),
+            })
+        } else if let Some((_content, answers)) = c {
+            Key::Choice(answers.clone(), true)
+        } else if q.kind == QuestionType::ShortAnswer {
+            Key::ShortAnswer(Accept::new(&q.answers, &q.meta))
+        } else if q.kind == QuestionType::Cloze {
+            Key::Cloze {
+                blanks: q
+                    .answers
+                    .iter()
+                    .map(|x| Accept::blank(x, &q.meta))
+                    .collect(),
+            }
+        } else if q.kind == QuestionType::Numeric {
+            Key::Numeric(Numeric::new(&q.answers[0].content).unwrap())
+        } else if q.kind == QuestionType::Essay {
+            Key::Essay {
+                rubric: q
+                    .answers
+                    .iter()
+                    .map(|x| Criterion::new(&x.content).unwrap())
+                    .collect(),
+            }
+        } else {
+            let mut c = answer_counter();
+            Key::Choice(
+                q.answers
+                    .iter()
+                    .filter_map(|x| {
+                        let answer = c.next().unwrap();
+                        if x.is_correct { Some(answer) } else { None }
+                    })
+                    .collect(),
+                false,
+            )
+        }
+    }
+
+    /**
+    Get the default number of points for the question
+    */
+    fn points(&self) -> f32 {
+        let n = match self {
+            Key::Choice(letters, _) => letters.len(),
+            Key::ShortAnswer(_) | Key::Numeric(_) => 1,
+            Key::Ordering(sequence) => sequence.order.len(),
+            Key::Cloze { blanks } => blanks.len(),
+            Key::Essay { rubric } => rubric.iter().map(|x| x.points).sum(),
+        };
+        f32::value_from(n).unwrap()
+    }
+
+    /**
+    Calculate the points earned for a response
+
+    The scoring policy is used for choice and match questions if it applies, and `options` is the
+    number of choices for a choice question, if known.
+    The result is scaled so that a fully correct response earns `points`.
+    Essay questions are graded manually, so always earn zero points here.
+    */
+    fn score(
+        &self,
+        response: &[String],
+        scoring: Option<Scoring>,
+        options: Option<usize>,
+        points: f32,
+    ) -> f32 {
+        let count = |n: usize| f32::value_from(n).unwrap() * points / self.points();
+        match self {
+            Key::Choice(letters, is_match) => {
+                let response = &Key::letters(response);
+                let kind = if *is_match {
+                    QuestionType::Match
+                } else {
+                    QuestionType::Choice
+                };
+                let scoring = scoring
+                    .filter(|x| x.applies(kind, letters.len()))
+                    .unwrap_or(Scoring::PerOption);
+                if *is_match {
+                    Key::score_match(letters, response, scoring, points)
+                } else {
+                    Key::score_choice(letters, response, scoring, options, points)
+                }
+            }
+            Key::ShortAnswer(accept) => {
+                if response.first().is_some_and(|x| accept.matches(x)) {
+                    points
+                } else {
+                    0.0
+                }
+            }
+            Key::Numeric(numeric) => {
+                if response.first().is_some_and(|x| numeric.matches(x)) {
+                    points
+                } else {
+                    0.0
+                }
+            }
+            Key::Ordering(sequence) => count(sequence.score(&Key::letters(response))),
+            Key::Cloze { blanks } => count(
+                blanks
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, blank)| response.get(*i).is_some_and(|x| blank.matches(x)))
+                    .count(),
+            ),
+            Key::Essay { .. } => 0.0,
+        }
+    }
+
+    /**
+    Split a choice or match question response written as a single string of letters (`AC`) into
+    separate letters
+    */
+    fn letters(response: &[String]) -> Vec<String> {
+        match response {
+            [x] if x.len() > 1 && x.chars().all(|c| c.is_ascii_uppercase()) => {
+                x.chars().map(String::from).collect()
+            }
+            _ => response.to_vec(),
+        }
+    }
+
+    /**
+    Check a response for problems
+
+    `options` is the number of choices for a choice question, if known.
+    */
+    fn validate(&self, response: &[String], options: Option<usize>) -> Vec<String> {
+        let mut problems = vec![];
+        let mut check_letters = |response: &[String], n: usize, unique: bool| {
+            let valid = answer_counter().take(n).collect::<Vec<_>>();
+            let mut seen = HashSet::new();
+            for letter in response {
+                if !valid.contains(letter) {
+                    problems.push(format!(
+                        "Letter `{letter}` is out of range (`{}` to `{}`)",
+                        valid[0],
+                        valid[n - 1],
+                    ));
+                } else if unique && !seen.insert(letter) {
+                    problems.push(format!("Duplicate letter `{letter}`"));
+                }
+            }
+        };
+        match self {
+            Key::Choice(letters, true) => {
+                let response = Key::letters(response);
+                check_letters(&response, letters.len(), false);
+                if response.len() != letters.len() {
+                    problems.push(format!(
+                        "Expected {} letters (one per item) but found {}",
+                        letters.len(),
+                        response.len(),
+                    ));
+                }
+            }
+            Key::Choice(_letters, false) => {
+                let response = Key::letters(response);
+                if let Some(options) = options {
+                    check_letters(&response, options, true);
+                } else {
+                    let mut seen = HashSet::new();
+                    for letter in response.iter().filter(|x| !seen.insert(*x)) {
+                        problems.push(format!("Duplicate letter `{letter}`"));
+                    }
+                }
+            }
+            Key::Ordering(sequence) => {
+                let response = Key::letters(response);
+                check_letters(&response, sequence.order.len(), true);
+                if response.len() != sequence.order.len() {
+                    problems.push(format!(
+                        "Expected {} letters (one per item) but found {}",
+                        sequence.order.len(),
+                        response.len(),
+                    ));
+                }
+            }
+            Key::ShortAnswer(_) | Key::Numeric(_) => {
+                if response.len() > 1 {
+                    problems.push(format!(
+                        "Expected a single answer but found {}",
+                        response.len(),
+                    ));
+                }
+            }
+            Key::Cloze { blanks } => {
+                if response.len() > blanks.len() {
+                    problems.push(format!(
+                        "Expected at most {} blanks but found {}",
+                        blanks.len(),
+                        response.len(),
+                    ));
+                }
+            }
+            Key::Essay { .. } => {}
+        }
+        problems
+    }
+
+    /**
+    Check an essay question's rubric scores for problems
+    */
+    fn validate_rubric(&self, scores: &[usize]) -> Vec<String> {
+        let Key::Essay { rubric } = self else {
+            return vec![String::from(
+                "Rubric scores for a question that is not an essay",
+            )];
+        };
+        let mut problems = vec![];
+        if scores.len() != rubric.len() {
+            problems.push(format!(
+                "Expected {} rubric scores (one per criterion) but found {}",
+                rubric.len(),
+                scores.len(),
+            ));
+        }
+        for (i, (score, criterion)) in scores.iter().zip(rubric).enumerate() {
+            if *score > criterion.points {
+                problems.push(format!(
+                    "Rubric score {score} for criterion {} is more than its {} {}",
+                    i + 1,
+                    criterion.points,
+                    if criterion.points == 1 {
+                        "point"
+                    } else {
+                        "points"
+                    },
+                ));
+            }
+        }
+        problems
+    }
+
+    /**
+    Calculate the points earned for a match question response
+    */
+    fn score_match(letters: &[String], response: &[String], scoring: Scoring, points: f32) -> f32 {
+        let wrong = letters
+            .iter()
+            .enumerate()
+            .filter(|(i, x)| response.get(*i) != Some(*x))
+            .count();
+        if scoring == Scoring::AllOrNothing {
+            if wrong == 0 { points } else { 0.0 }
+        } else {
+            points
+                - f32::value_from(wrong).unwrap() * points / f32::value_from(letters.len()).unwrap()
+        }
+    }
+
+    /**
+    Calculate the points earned for a single or multiple answer question response
+    */
+    fn score_choice(
+        letters: &[String],
+        response: &[String],
+        scoring: Scoring,
+        options: Option<usize>,
+        points: f32,
+    ) -> f32 {
+        let count = |n: usize| f32::value_from(n).unwrap();
+        let k = count(letters.len());
+        let right = count(response.iter().filter(|x| letters.contains(x)).count());
+        let wrong = count(response.iter().filter(|x| !letters.contains(x)).count());
+        let missing = count(letters.iter().filter(|x| !response.contains(x)).count());
+        let incorrect = options.map(|n| n.saturating_sub(letters.len()));
+        match scoring {
+            Scoring::AllOrNothing => {
+                if wrong == 0.0 && missing == 0.0 {
+                    points
+                } else {
+                    0.0
+                }
+            }
+            Scoring::RightMinusWrong => (right - wrong).max(0.0) * points / k,
+            Scoring::Fractional => {
+                let penalty = match incorrect {
+                    Some(incorrect) if incorrect > 0 => wrong / count(incorrect),
+                    _ => wrong / k,
+                };
+                (right / k - penalty).max(0.0) * points
+            }
+            Scoring::NegativeMarking => {
+                if response.is_empty() {
+                    0.0
+                } else if response == letters {
+                    points
+                } else {
+                    -points / count(incorrect.unwrap_or(1).max(1))
+                }
+            }
+            _ => {
+                if letters.len() == 1 {
+                    // Single answer
+                    if response == letters { points } else { 0.0 }
+                } else {
+                    // Multiple answer
+                    (k - wrong - missing) * points / k
+                }
+            }
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Accepted short answers
+
+A response is correct if it equals any of the `answers` or fully matches any of the `patterns`
+(regular expressions), optionally ignoring case and treating any run of whitespace as a single
+space; leading and trailing whitespace is always ignored.
+*/
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Accept {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    answers: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    patterns: Vec<String>,
+
+    ignore_case: bool,
+    normalize_whitespace: bool,
+}
+
+impl Accept {
+    /**
+    Create from a short answer question's answers
+    */
+    fn new(answers: &[Answer], meta: &QuestionMeta) -> Accept {
+        let (patterns, answers): (Vec<_>, Vec<_>) =
+            answers.iter().partition(|x| x.form == Some(Form::Pattern));
+        Accept {
+            answers: answers.into_iter().map(|x| x.content.clone()).collect(),
+            patterns: patterns.into_iter().map(|x| x.content.clone()).collect(),
+            ignore_case: meta.ignore_case(),
+            normalize_whitespace: meta.normalize_whitespace(),
+        }
+    }
+
+    /**
+    Create from a cloze blank ([`Answer::blank()`])
+    */
+    fn blank(answer: &Answer, meta: &QuestionMeta) -> Accept {
+        if answer.form == Some(Form::Pattern) {
+            Accept::new(std::slice::from_ref(answer), meta)
+        } else {
+            Accept {
+                answers: answer
+                    .content
+                    .split('|')
+                    .map(|x| x.trim().to_string())
+                    .collect(),
+                patterns: vec![],
+                ignore_case: meta.ignore_case(),
+                normalize_whitespace: meta.normalize_whitespace(),
+            }
+        }
+    }
+
+    /**
+    Check that a pattern is valid
+
+    On error, returns an explanation.
+    */
+    fn check(pattern: &str) -> std::result::Result<(), String> {
+        Accept::regex(pattern, false).map(|_re| ()).map_err(|e| {
+            let e = e.to_string();
+            let e = e.lines().last().unwrap_or_default();
+            format!(
+                "Invalid pattern `{pattern}`: {}",
+                e.trim_start_matches("error: ")
+            )
+        })
+    }
+
+    /**
+    Compile a pattern so that it must match the entire response
+    */
+    fn regex(pattern: &str, ignore_case: bool) -> std::result::Result<Regex, regex::Error> {
+        RegexBuilder::new(&format!("^(?:{pattern})$"))
+            .case_insensitive(ignore_case)
+            .build()
+    }
+
+    /**
+    Trim and optionally collapse whitespace
+    */
+    fn whitespace(&self, s: &str) -> String {
+        if self.normalize_whitespace {
+            s.split_whitespace().collect::<Vec<_>>().join(" ")
+        } else {
+            s.trim().to_string()
+        }
+    }
+
+    /**
+    Return true if the response is accepted
+    */
+    fn matches(&self, response: &str) -> bool {
+        let response = self.whitespace(response);
+        let eq = |x: &String| {
+            let x = self.whitespace(x);
+            if self.ignore_case {
+                x.to_lowercase() == response.to_lowercase()
+            } else {
+                x == response
+            }
+        };
+        self.answers.iter().any(eq)
+            || self
+                .patterns
+                .iter()
+                .any(|x| Accept::regex(x, self.ignore_case).is_ok_and(|re| re.is_match(&response)))
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Numeric answer
+
+Written as a number, an optional unit, and an optional absolute (`± 0.01`) or relative (`± 5%`)
+tolerance, for example `3.14 ± 0.01` or `240 miles`; `+/-` may be used instead of `±`.
+*/
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Numeric {
+    value: f64,
+
+    #[serde(default)]
+    tolerance: f64,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    relative: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    unit: Option<String>,
+}
+
+impl Numeric {
+    /**
+    Parse a numeric answer
+
+    On error, returns an explanation.
+    */
+    fn new(s: &str) -> std::result::Result<Numeric, String> {
+        let (answer, tolerance) = match s.split_once('±').or_else(|| s.split_once("+/-")) {
+            Some((answer, tolerance)) => (answer, Some(tolerance.trim())),
+            None => (s, None),
+        };
+        let Some((value, unit)) = Numeric::split(answer) else {
+            return Err(format!("Invalid numeric answer: `{}`", s.trim()));
+        };
+        let (tolerance, relative) = match tolerance {
+            Some(t) => {
+                let (t, relative) = match t.strip_suffix('%') {
+                    Some(t) => (t.trim(), true),
+                    None => (t, false),
+                };
+                match t.parse::<f64>() {
+                    Ok(x) if x.is_finite() && x >= 0.0 => (x, relative),
+                    _ => return Err(format!("Invalid tolerance: `{t}`")),
+                }
+            }
+            None => (0.0, false),
+        };
+        Ok(Numeric {
+            value,
+            tolerance,
+            relative,
+            unit,
+        })
+    }
+
+    /**
+    Split a number and an optional unit
+    */
+    fn split(s: &str) -> Option<(f64, Option<String>)> {
+        let s = s.trim();
+        s.char_indices()
+            .map(|(i, _c)| i)
+            .chain([s.len()])
+            .rev()
+            .find_map(|i| {
+                s[..i]
+                    .trim_end()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|x| x.is_finite())
+                    .map(|x| (x, s[i..].trim()))
+            })
+            .map(|(x, unit)| (x, (!unit.is_empty()).then(|| unit.to_string())))
+    }
+
+    /**
+    Return true if the response is within the tolerance
+
+    The response is a number optionally followed by the unit (ignoring case).
+    */
+    fn matches(&self, response: &str) -> bool {
+        let Some((x, unit)) = Numeric::split(response) else {
+            return false;
+        };
+        if unit.is_some_and(|unit| {
+            self.unit
+                .as_ref()
+                .is_none_or(|x| x.to_lowercase() != unit.to_lowercase())
+        }) {
+            return false;
+        }
+        let tolerance = if self.relative {
+            self.value.abs() * self.tolerance / 100.0
+        } else {
+            self.tolerance
+        };
+
+        // Allow for floating point rounding error
+        (x - self.value).abs() <= tolerance + 1e-9 * self.value.abs().max(1.0)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Scoring policy for a question
+
+Set per question via the `scoring` metadata or per quiz via the completed quiz's `scoring`
+([`Class`]).
+Each policy only applies to some question types ([`Scoring::applies()`]).
+*/
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scoring {
+    /// Full credit only if the whole answer is correct (choice, match, and ordering questions)
+    AllOrNothing,
+
+    /**
+    One point per correct answer, minus one point per missing or extra selection (default for
+    choice and match questions)
+    */
+    PerOption,
+
+    /**
+    One point per correct selection minus one point per incorrect selection, with a floor of zero
+    (choice questions)
+    */
+    RightMinusWrong,
+
+    /**
+    Fraction of the correct choices selected minus the fraction of the incorrect choices selected,
+    with a floor of zero, times the question's points (choice questions)
+    */
+    Fractional,
+
+    /**
+    Full credit for the correct answer, no credit for no answer, and a penalty of the question's
+    points divided by the number of incorrect choices for an incorrect answer (single answer
+    questions)
+    */
+    NegativeMarking,
+
+    /// One point per item in the correct position (default for ordering questions)
+    PerPosition,
+
+    /// One point per item in the longest subsequence of items in the correct relative order
+    LongestSubsequence,
+}
+
+impl Scoring {
+    /**
+    Return true if the scoring policy applies to a question type
+
+    `correct` is the number of correct answers.
+    */
+    #[must_use]
+    pub fn applies(self, kind: QuestionType, correct: usize) -> bool {
+        match self {
+            Scoring::AllOrNothing => matches!(
+                kind,
+                QuestionType::Choice | QuestionType::Match | QuestionType::Ordering,
+            ),
+            Scoring::PerOption => matches!(kind, QuestionType::Choice | QuestionType::Match),
+            Scoring::RightMinusWrong | Scoring::Fractional => kind == QuestionType::Choice,
+            Scoring::NegativeMarking => kind == QuestionType::Choice && correct == 1,
+            Scoring::PerPosition | Scoring::LongestSubsequence => kind == QuestionType::Ordering,
+        }
+    }
+}
+
+impl std::str::FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Scoring, String> {
+        match s {
+            "all-or-nothing" => Ok(Scoring::AllOrNothing),
+            "per-option" => Ok(Scoring::PerOption),
+            "right-minus-wrong" => Ok(Scoring::RightMinusWrong),
+            "fractional" => Ok(Scoring::Fractional),
+            "negative-marking" => Ok(Scoring::NegativeMarking),
+            "per-position" => Ok(Scoring::PerPosition),
+            "longest-subsequence" => Ok(Scoring::LongestSubsequence),
+            _ => Err(String::from(
+                "expected `all-or-nothing`, `per-option`, `right-minus-wrong`, `fractional`, \
+                `negative-marking`, `per-position`, or `longest-subsequence`",
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Scoring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Scoring::AllOrNothing => "all-or-nothing",
+                Scoring::PerOption => "per-option",
+                Scoring::RightMinusWrong => "right-minus-wrong",
+                Scoring::Fractional => "fractional",
+                Scoring::NegativeMarking => "negative-marking",
+                Scoring::PerPosition => "per-position",
+                Scoring::LongestSubsequence => "longest-subsequence",
+            },
+        )
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Correct order of an ordering question's items
+*/
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Sequence {
+    order: Vec<String>,
+
+    scoring: Scoring,
+}
+
+impl Sequence {
+    /**
+    Calculate the score for a response (item letters in the student's order)
+    */
+    fn score(&self, response: &[String]) -> usize {
+        match self.scoring {
+            Scoring::AllOrNothing => {
+                if response == self.order {
+                    self.order.len()
+                } else {
+                    0
+                }
+            }
+            Scoring::LongestSubsequence => {
+                // Longest common subsequence
+                let mut lengths = vec![vec![0; response.len() + 1]; self.order.len() + 1];
+                for (i, a) in self.order.iter().enumerate() {
+                    for (j, b) in response.iter().enumerate() {
+                        lengths[i + 1][j + 1] = if a == b {
+                            lengths[i][j] + 1
+                        } else {
+                            lengths[i][j + 1].max(lengths[i + 1][j])
+                        };
                     }
-                    (x, Some((answers_content, key)))
+                }
+                lengths[self.order.len()][response.len()]
+            }
+            _ => self
+                .order
+                .iter()
+                .zip(response)
+                .filter(|(a, b)| a == b)
+                .count(),
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Essay question rubric criterion
+
+Written as the point value followed by the description, for example `3 Clear thesis statement`.
+*/
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Criterion {
+    points: usize,
+    description: String,
+}
+
+impl Criterion {
+    /**
+    Parse a rubric criterion
+
+    On error, returns an explanation.
+    */
+    fn new(s: &str) -> std::result::Result<Criterion, String> {
+        let s = s.trim();
+        let (points, description) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        match points.parse() {
+            Ok(points) if !description.trim().is_empty() => Ok(Criterion {
+                points,
+                description: description.trim().to_string(),
+            }),
+            _ => Err(format!(
+                "Invalid rubric criterion `{s}` (expected `* + POINTS DESCRIPTION`)",
+            )),
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Letter grade scale
+
+Each grade has a label (`A-`, `Pass`, `6.0`, ...) and a minimum percentage score; a percentage
+score earns the grade with the highest minimum it meets, or the grade with the lowest minimum if it
+does not meet any.
+In a completed quiz file, a grade scale is a JSON object that maps each label to its minimum;
+Its [`FromStr`](std::str::FromStr) implementation parses a comma-separated list of `LABEL=MIN`.
+*/
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "BTreeMap<String, f32>")]
+pub struct GradeScale {
+    grades: Vec<(f32, String)>,
+}
+
+impl GradeScale {
+    /**
+    Load from a JSON file
+
+    # Errors
+
+    Returns an error if not able to read the file at the given path, deserialize it from JSON, or
+    it is not a valid grade scale
+    */
+    pub fn from(path: &Path) -> Result<GradeScale> {
+        match serde_json::from_str(&std::fs::read_to_string(path)?) {
+            Ok(grades) => Ok(grades),
+            Err(e) => Err(anyhow!(format!(
+                "Could not parse {:?}: {e}",
+                path.display(),
+            ))),
+        }
+    }
+
+    /**
+    Get the grade for a percentage score
+    */
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn grade(&self, pct: f32) -> &str {
+        self.grades
+            .iter()
+            .find(|(min, _label)| pct >= *min)
+            .or(self.grades.last())
+            .map(|(_min, label)| label.as_str())
+            .unwrap()
+    }
+
+    /**
+    Iterate the grade labels, from highest to lowest
+    */
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.grades.iter().map(|(_min, label)| label.as_str())
+    }
+}
+
+impl Default for GradeScale {
+    /**
+    A (90%), B (80%), C (70%), D (60%), F
+    */
+    fn default() -> GradeScale {
+        GradeScale {
+            grades: LETTER_GRADES
+                .iter()
+                .map(|(min, label)| (*min, (*label).to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<BTreeMap<String, f32>> for GradeScale {
+    type Error = String;
+
+    fn try_from(grades: BTreeMap<String, f32>) -> std::result::Result<GradeScale, String> {
+        if grades.is_empty() {
+            return Err(String::from("A grade scale must have at least one grade"));
+        }
+        let mut grades = grades
+            .into_iter()
+            .map(|(label, min)| {
+                if label.trim().is_empty() {
+                    Err(String::from("Grade labels cannot be empty"))
+                } else if !min.is_finite() {
+                    Err(format!("Invalid minimum for grade `{label}`: `{min}`"))
                 } else {
-                    (x, None)
+                    Ok((min, label.trim().to_string()))
                 }
             })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        grades.sort_by(|a, b| b.0.total_cmp(&a.0));
+        if let Some(w) = grades
+            .windows(2)
+            .find(|w| w[0].0.total_cmp(&w[1].0).is_eq())
+        {
+            return Err(format!(
+                "Grades `{}` and `{}` have the same minimum ({})",
+                w[0].1, w[1].1, w[0].0,
+            ));
+        }
+        Ok(GradeScale { grades })
+    }
+}
+
+impl std::str::FromStr for GradeScale {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<GradeScale, String> {
+        let mut grades = BTreeMap::new();
+        for item in s.split(',') {
+            let Some((label, min)) = item.rsplit_once('=') else {
+                return Err(format!("Expected `LABEL=MIN`: `{}`", item.trim()));
+            };
+            let min = min
+                .trim()
+                .parse()
+                .map_err(|e| format!("Invalid minimum for grade `{}`: {e}", label.trim()))?;
+            if grades.insert(label.trim().to_string(), min).is_some() {
+                return Err(format!("Duplicate grade `{}`", label.trim()));
+            }
+        }
+        GradeScale::try_from(grades)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Column mapping for loading a completed quiz from a CSV file ([`Class::from_csv()`])
+
+Each column is identified by its header.
+By default, the student's name is in the `Name` or `Student` column (or the first column), the
+version is in the `Version` column (if any), and each column whose header is a question number
+(`1`, `Q1`, `Question 1`, ...) is that question.
+Its [`FromStr`](std::str::FromStr) implementation parses a comma-separated list of `name=HEADER`,
+`version=HEADER`, and `N=HEADER` (question `N`); if any questions are given, the other columns are
+ignored.
+*/
+#[derive(Clone, Debug, Default)]
+pub struct Columns {
+    name: Option<String>,
+    version: Option<String>,
+    questions: BTreeMap<usize, String>,
+}
+
+impl Columns {
+    /**
+    Find the name, version, and question column indices in a header
+    */
+    #[allow(clippy::type_complexity)]
+    fn resolve(
+        &self,
+        header: &[String],
+    ) -> std::result::Result<(usize, Option<usize>, BTreeMap<usize, usize>), String> {
+        let find = |column: &str| {
+            header
+                .iter()
+                .position(|x| x == column)
+                .ok_or_else(|| format!("Missing column `{column}`"))
+        };
+        let find_any = |columns: &[&str]| {
+            header
+                .iter()
+                .position(|x| columns.iter().any(|c| x.eq_ignore_ascii_case(c)))
+        };
+        let name = match &self.name {
+            Some(column) => find(column)?,
+            None => find_any(&["name", "student"]).unwrap_or(0),
+        };
+        let version = match &self.version {
+            Some(column) => Some(find(column)?),
+            None => find_any(&["version"]),
+        };
+        let questions = if self.questions.is_empty() {
+            let number = Regex::new(r"(?i)^(?:q|question)?\s*(\d+)$").unwrap();
+            let mut questions = BTreeMap::new();
+            for (i, column) in header.iter().enumerate() {
+                if i == name || Some(i) == version {
+                    continue;
+                }
+                let Some(q) = number
+                    .captures(column.trim())
+                    .and_then(|x| x[1].parse::<usize>().ok())
+                else {
+                    continue;
+                };
+                if let Some(j) = questions.insert(q, i) {
+                    return Err(format!(
+                        "Columns `{}` and `{column}` are both question {q}",
+                        header[j],
+                    ));
+                }
+            }
+            questions
+        } else {
+            self.questions
+                .iter()
+                .map(|(q, column)| Ok((*q, find(column)?)))
+                .collect::<std::result::Result<_, String>>()?
+        };
+        Ok((name, version, questions))
+    }
+}
+
+impl std::str::FromStr for Columns {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Columns, String> {
+        let mut columns = Columns::default();
+        for item in s.split(',') {
+            let Some((field, column)) = item.split_once('=') else {
+                return Err(format!("Expected `FIELD=HEADER`: `{}`", item.trim()));
+            };
+            let column = column.trim().to_string();
+            match field.trim() {
+                "name" => columns.name = Some(column),
+                "version" => columns.version = Some(column),
+                q => {
+                    let q = q.parse().map_err(|_| {
+                        format!("Expected `name`, `version`, or a question number: `{q}`")
+                    })?;
+                    if columns.questions.insert(q, column).is_some() {
+                        return Err(format!("Duplicate question `{q}`"));
+                    }
+                }
+            }
+        }
+        Ok(columns)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+How to treat unanswered questions when checking responses ([`Class::validate()`])
+*/
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Blanks {
+    /// Warn and grade as wrong
+    #[default]
+    Wrong,
+
+    /// Report as errors
+    Error,
+}
+
+impl std::str::FromStr for Blanks {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Blanks, String> {
+        match s {
+            "wrong" => Ok(Blanks::Wrong),
+            "error" => Ok(Blanks::Error),
+            _ => Err(String::from("expected `wrong` or `error`")),
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Problem with a student's responses found by [`Class::validate()`]
+*/
+#[derive(Clone, Debug)]
+pub struct Problem {
+    student: String,
+    question: Option<usize>,
+    severity: Severity,
+    message: String,
+}
+
+impl Problem {
+    /**
+    Create a new problem
+    */
+    fn new(student: &str, question: Option<usize>, severity: Severity, message: &str) -> Problem {
+        Problem {
+            student: student.to_string(),
+            question,
+            severity,
+            message: message.to_string(),
+        }
+    }
+
+    /**
+    Get the student's name
+    */
+    #[must_use]
+    pub fn student(&self) -> &str {
+        &self.student
+    }
+
+    /**
+    Get the question number, if the problem is with a particular question
+    */
+    #[must_use]
+    pub fn question(&self) -> Option<usize> {
+        self.question
+    }
+
+    /**
+    Get the severity
+    */
+    #[must_use]
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /**
+    Get the message
+    */
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.student)?;
+        if let Some(question) = self.question {
+            write!(f, ": question {question}")?;
+        }
+        write!(f, ": {}: {}", self.severity, self.message)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Completed quiz
+
+Essay questions are graded manually: `rubric` maps each student's name to the score for each
+rubric criterion of each essay question.
+The optional `scoring` policy applies to every choice and match question that does not specify
+its own, and the optional `grades` scale replaces the default letter grade scale.
+*/
+#[derive(Default, Deserialize)]
+pub struct Class {
+    description: String,
+    students: BTreeMap<String, BTreeMap<usize, Vec<String>>>,
+
+    #[serde(default)]
+    versions: BTreeMap<String, String>,
+
+    #[serde(default)]
+    rubric: BTreeMap<String, BTreeMap<usize, Vec<usize>>>,
+
+    #[serde(default)]
+    scoring: Option<Scoring>,
+
+    #[serde(default)]
+    grades: GradeScale,
+
+    #[serde(skip)]
+    total: f32,
+
+    #[serde(skip)]
+    questions: usize,
+
+    #[serde(skip)]
+    scores: BTreeMap<String, (f32, Vec<usize>)>,
+
+    #[serde(skip)]
+    items: BTreeMap<String, BTreeMap<usize, (f32, f32)>>,
+
+    #[serde(skip)]
+    choices: BTreeMap<usize, (Vec<String>, Option<usize>)>,
+}
+
+impl Class {
+    /**
+    Load from a JSON file
+
+    # Errors
+
+    Returns an error if not able to read the file at the given path and deserialize it from JSON,
+    or there are no students
+    */
+    pub fn from(path: &Path) -> Result<Class> {
+        let class: Class = match serde_json::from_str(&std::fs::read_to_string(path)?) {
+            Ok(answers) => answers,
+            Err(e) => {
+                return Err(anyhow!(format!(
+                    "Could not parse {:?}: {e}",
+                    path.display(),
+                )));
+            }
+        };
+        if class.students.is_empty() {
+            return Err(anyhow!(format!("No students in {:?}", path.display())));
+        }
+        Ok(class)
+    }
+
+    /**
+    Load from a CSV file
+
+    The first row is the header, and each other row is a student's responses.
+    Multiple letters for a question are written as `A;C` or `AC`, and blank cells are unanswered
+    questions.
+    The description is the file name without the extension.
+
+    # Errors
+
+    Returns an error if not able to read or parse the file at the given path, the columns do not
+    match the header, or there are no students
+    */
+    pub fn from_csv(path: &Path, columns: &Columns) -> Result<Class> {
+        let error =
+            |e: &dyn fmt::Display| anyhow!(format!("Could not parse {:?}: {e}", path.display()));
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_path(path)
+            .map_err(|e| error(&e))?;
+        let header = reader
+            .headers()
+            .map_err(|e| error(&e))?
+            .iter()
+            .map(|x| x.trim().to_string())
             .collect::<Vec<_>>();
+        let (name, version, questions) = columns.resolve(&header).map_err(|e| error(&e))?;
 
-        Quiz { questions }
+        let mut class = Class {
+            description: path
+                .file_stem()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            ..Class::default()
+        };
+        for (i, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| error(&e))?;
+            let row = i + 2;
+            let Some(student) = record.get(name).map(str::trim).filter(|x| !x.is_empty()) else {
+                return Err(error(&format!("Missing student name in row {row}")));
+            };
+            let responses = questions
+                .iter()
+                .filter_map(|(q, column)| {
+                    let cell = record.get(*column)?.trim();
+                    if cell.is_empty() {
+                        None
+                    } else {
+                        Some((*q, cell.split(';').map(|x| x.trim().to_string()).collect()))
+                    }
+                })
+                .collect();
+            if class
+                .students
+                .insert(student.to_string(), responses)
+                .is_some()
+            {
+                return Err(error(&format!(
+                    "Duplicate student `{student}` in row {row}"
+                )));
+            }
+            if let Some(version) = version
+                .and_then(|x| record.get(x))
+                .map(str::trim)
+                .filter(|x| !x.is_empty())
+            {
+                class
+                    .versions
+                    .insert(student.to_string(), version.to_string());
+            }
+        }
+        if class.students.is_empty() {
+            return Err(anyhow!(format!("No students in {:?}", path.display())));
+        }
+        Ok(class)
     }
 
     /**
-    Generate quiz markdown
+    Check each student's responses against an answer key
+
+    Reports unknown versions and questions, letters outside a question's range, duplicate letters,
+    the wrong number of letters for match and ordering questions, extra answers, rubric scores for
+    questions that are not essays, the wrong number of rubric scores, and rubric scores above a
+    criterion's points, and unanswered questions (including essays without rubric scores), which
+    are errors or warnings (graded as wrong) depending on `blanks`.
     */
-    #[allow(clippy::missing_panics_doc)]
     #[must_use]
-    pub fn markdown(&self) -> String {
-        self.questions
-            .par_iter()
-            .enumerate()
-            .map(|(i, (q, c))| {
-                if let Some((content, _answers)) = c {
-                    let pre = format!("{}. ", i + 1);
-                    let sep = format!("\n\n{}", " ".repeat(pre.len()));
-                    format!(
-                        "{pre}{}\n\n{content}\n{}\n",
-                        q.content
-                            .iter()
-                            .map(|x| x.replace('\n', &sep[1..]))
-                            .collect::<Vec<_>>()
-                            .join(&sep),
-                        q.answers.iter().fold(String::new(), |mut s, x| {
-                            writeln!(s, "    * _____ {}", x.content).unwrap();
-                            s
-                        }),
-                    )
+    pub fn validate(&self, answers: &Answers, blanks: Blanks) -> Vec<Problem> {
+        let mut problems = vec![];
+        for (name, quiz) in &self.students {
+            let version = self.versions.get(name).map(String::as_str);
+            let Some(answers) = answers.version(version) else {
+                problems.push(Problem::new(
+                    name,
+                    None,
+                    Severity::Error,
+                    &version.map_or(String::from("Missing version"), |x| {
+                        format!("Unknown version `{x}`")
+                    }),
+                ));
+                continue;
+            };
+            for (q, response) in quiz {
+                let Some(key) = answers.get(*q) else {
+                    problems.push(Problem::new(
+                        name,
+                        Some(*q),
+                        Severity::Error,
+                        "Unknown question",
+                    ));
+                    continue;
+                };
+                for message in key.validate(response, answers.options(*q)) {
+                    problems.push(Problem::new(name, Some(*q), Severity::Error, &message));
+                }
+            }
+            let rubric = self.rubric.get(name);
+            for (q, key) in &answers.answers {
+                let (unanswered, message) = if matches!(key, Key::Essay { .. }) {
+                    (rubric.and_then(|x| x.get(q)).is_none(), "No rubric scores")
                 } else {
-                    let mut c = answer_counter();
-                    let pre = format!("{}. ", i + 1);
-                    let sep = format!("\n\n{}", " ".repeat(pre.len()));
-                    format!(
-                        "{pre}{}\n\n{}",
-                        q.content
-                            .iter()
-                            .map(|x| x.replace('\n', &sep[1..]))
-                            .collect::<Vec<_>>()
-                            .join(&sep),
-                        q.answers.iter().fold(String::new(), |mut s, x| {
-                            writeln!(s, "    * [ ] {}. {}\n", c.next().unwrap(), x.content)
-                                .unwrap();
-                            s
-                        }),
+                    (
+                        quiz.get(q)
+                            .is_none_or(|x| x.iter().all(|x| x.trim().is_empty())),
+                        "Unanswered",
                     )
+                };
+                if unanswered {
+                    problems.push(match blanks {
+                        Blanks::Wrong => Problem::new(
+                            name,
+                            Some(*q),
+                            Severity::Warning,
+                            &format!("{message} (graded as wrong)"),
+                        ),
+                        Blanks::Error => Problem::new(name, Some(*q), Severity::Error, message),
+                    });
                 }
-            })
-            .collect::<String>()
+            }
+            for (q, scores) in rubric.into_iter().flatten() {
+                let messages = match answers.get(*q) {
+                    Some(key) => key.validate_rubric(scores),
+                    None => vec![String::from("Unknown question")],
+                };
+                for message in messages {
+                    problems.push(Problem::new(name, Some(*q), Severity::Error, &message));
+                }
+            }
+        }
+        problems
     }
 
     /**
-    Generate quiz [`Answers`]
+    Set the grade scale
     */
-    #[must_use]
-    pub fn answers(&self) -> Answers {
-        Answers::new(self)
+    pub fn set_grades(&mut self, grades: GradeScale) {
+        self.grades = grades;
     }
-}
 
-//--------------------------------------------------------------------------------------------------
+    /**
+    Compute the scores
 
-/**
-Quiz answer key
+    If the answer key has multiple versions, each student's version is looked up in `versions`, and
+    the questions in the grade report and exports are numbered in the canonical order.
 
-This struct is used in two ways:
+    # Panics
 
-1. Generated via [`Quiz::answers()`] during quiz generation in order to generate
-   content for the answer key (`answers.json`) and quiz with answers
-   (`answers.md`) files
-2. Loaded from a saved answer key (`answers.json`) for grading a quiz
-*/
-#[derive(Debug)]
-pub struct Answers {
-    answers: BTreeMap<usize, (Vec<String>, bool)>,
-    markdown: Option<String>,
-}
+    Panics if not able to resolve a student's version or the question answers
+    */
+    pub fn grade(&mut self, answers: &Answers) {
+        self.total = answers.total();
+        self.questions = answers.questions();
+        self.scores = BTreeMap::new();
+        self.items = BTreeMap::new();
+
+        // Choice questions for the distractor table (each version orders the choices differently)
+        self.choices = answers
+            .answers
+            .iter()
+            .filter_map(|(q, key)| match key {
+                Key::Choice(letters, false) => Some((*q, (letters.clone(), answers.options(*q)))),
+                _ => None,
+            })
+            .collect();
+
+        for (name, quiz) in &self.students {
+            let version = self.versions.get(name).map(String::as_str);
+            let answers = answers
+                .version(version)
+                .unwrap_or_else(|| panic!("Unknown version for {name:?}: {version:?}"));
+            let mut missed = 0.0;
+            let mut wrong = BTreeSet::new();
+            let mut items = BTreeMap::new();
+            for (q, a) in quiz {
+                let Some(key) = answers.get(*q) else {
+                    continue;
+                };
+                if matches!(key, Key::Essay { .. }) {
+                    continue;
+                }
+                let points = answers.points(*q).unwrap();
+                let scoring = answers
+                    .meta(*q)
+                    .and_then(QuestionMeta::scoring)
+                    .or(self.scoring);
+                let score = key.score(a, scoring, answers.options(*q), points);
+                if score < points {
+                    missed += points - score;
+                    wrong.insert(answers.canonical(*q).unwrap_or(*q));
+                }
+                if points > 0.0 {
+                    items.insert(answers.canonical(*q).unwrap_or(*q), (score, points));
+                }
+            }
+
+            // Unanswered
+            for (q, key) in &answers.answers {
+                if !quiz.contains_key(q) && !matches!(key, Key::Essay { .. }) {
+                    let points = answers.points(*q).unwrap();
+                    missed += points;
+                    wrong.insert(answers.canonical(*q).unwrap_or(*q));
+                    items.insert(answers.canonical(*q).unwrap_or(*q), (0.0, points));
+                }
+            }
+
+            // Essay (manually graded; scores are capped at each criterion's points and scaled to
+            // the question's points)
+            for (q, key) in &answers.answers {
+                if let Key::Essay { rubric } = key {
+                    let scale = answers.points(*q).unwrap() / key.points();
+                    let scores = self.rubric.get(name).and_then(|x| x.get(q));
+                    let mut earned = 0;
+                    for (i, criterion) in rubric.iter().enumerate() {
+                        let score = scores
+                            .and_then(|x| x.get(i))
+                            .map_or(0, |x| (*x).min(criterion.points));
+                        earned += score;
+                        if score < criterion.points {
+                            missed += f32::value_from(criterion.points - score).unwrap() * scale;
+                            wrong.insert(answers.canonical(*q).unwrap_or(*q));
+                        }
+                    }
+                    if key.points() > 0.0 {
+                        items.insert(
+                            answers.canonical(*q).unwrap_or(*q),
+                            (
+                                f32::value_from(earned).unwrap() * scale,
+                                key.points() * scale,
+                            ),
+                        );
+                    }
+                }
+            }
+
+            self.scores.insert(
+                name.clone(),
+                (self.total - missed, wrong.into_iter().collect()),
+            );
+            self.items.insert(name.clone(), items);
+        }
+        if !answers.versions.is_empty() {
+            self.choices = BTreeMap::new();
+        }
+    }
 
-impl Answers {
     /**
-    Create a new quiz answer key
+    Generate the grade report markdown
+
+    # Panics
+
+    Panics if not able to resolve the wrong answers or there are no students
     */
-    fn new(quiz: &Quiz) -> Answers {
-        let answers: BTreeMap<usize, (Vec<String>, bool)> = quiz
-            .questions
-            .par_iter()
-            .enumerate()
-            .map(|(i, (q, c))| {
-                if let Some((_content, answers)) = c {
-                    (i + 1, (answers.clone(), true))
+    #[must_use]
+    pub fn markdown(&self) -> String {
+        let scores = self.ranked();
+        let summary = self.summary(&scores);
+        let pct = |x: f32| x / self.total * 100.0;
+
+        let mut grades = Veg::table("Name|Score|Percent|Grade|Questions\n-|-:|-:|-|-");
+        for (score, name, wrong) in &scores {
+            let pct = pct(*score);
+            grades.push(Box::new(Grade::new(
+                name,
+                *score,
+                pct,
+                self.grades.grade(pct),
+                wrong,
+            )));
+        }
+
+        let mut stats = Veg::table("Description|Value|Percent|Grade\n-|-:|-:|-");
+        let with_grade = |x: f32| (Some(pct(x)), Some(self.grades.grade(pct(x))));
+        let reliability = format!("Reliability ({})", summary.reliability_method);
+        for (description, value, (percent, grade)) in [
+            (
+                "Number of students",
+                Some(f32::value_from(summary.students).unwrap()),
+                (None, None),
+            ),
+            (
+                "Number of questions",
+                Some(f32::value_from(summary.questions).unwrap()),
+                (None, None),
+            ),
+            ("Total points", Some(self.total), (None, None)),
+            ("High score", Some(summary.high), with_grade(summary.high)),
+            ("Low score", Some(summary.low), with_grade(summary.low)),
+            ("Mean score", Some(summary.mean), with_grade(summary.mean)),
+            (
+                "Median score",
+                Some(summary.median),
+                with_grade(summary.median),
+            ),
+            (
+                "First quartile",
+                Some(summary.first_quartile),
+                (Some(pct(summary.first_quartile)), None),
+            ),
+            (
+                "Third quartile",
+                Some(summary.third_quartile),
+                (Some(pct(summary.third_quartile)), None),
+            ),
+            ("Standard deviation", Some(summary.std_dev), (None, None)),
+            (&reliability, summary.reliability, (None, None)),
+            ("Standard error of measurement", summary.sem, (None, None)),
+        ] {
+            stats.push(Box::new(Stat::new(description, value, percent, grade)));
+        }
+        for x in &summary.grades {
+            let count = f32::value_from(x.count).unwrap();
+            stats.push(Box::new(Stat::new(
+                &x.grade,
+                Some(count),
+                Some(count / f32::value_from(summary.students).unwrap() * 100.0),
+                None,
+            )));
+        }
+
+        format!(
+            "# {}\n\n{}\n{}\n{}",
+            self.description,
+            grades.markdown().unwrap(),
+            stats.markdown().unwrap(),
+            self.item_analysis(&scores),
+        )
+    }
+
+    /**
+    Generate the feedback markdown for each student
+
+    Each student's feedback has their score and each question they missed with their response
+    and the correct answer (as in `answers.md`).
+    Answer keys written by quixote 0.7 and earlier do not record the questions, so only the
+    question numbers and responses are included.
+
+    # Panics
+
+    Panics if not able to resolve a student's version
+    */
+    #[must_use]
+    pub fn feedback(&self, answers: &Answers) -> BTreeMap<String, String> {
+        self.scores
+            .iter()
+            .map(|(name, (score, wrong))| {
+                let version = self.versions.get(name).map(String::as_str);
+                let answers = answers
+                    .version(version)
+                    .unwrap_or_else(|| panic!("Unknown version for {name:?}: {version:?}"));
+                let pct = score / self.total * 100.0;
+                let mut s = format!(
+                    "# {}: {name}\n\nScore: **{}** of {} ({}, {})\n\n",
+                    self.description,
+                    fmt_score(*score),
+                    fmt_score(self.total),
+                    fmt_percent(pct),
+                    self.grades.grade(pct),
+                );
+                // Missed questions, numbered as in the student's version
+                let wrong = answers
+                    .answers
+                    .keys()
+                    .filter(|q| wrong.contains(&answers.canonical(**q).unwrap_or(**q)))
+                    .collect::<Vec<_>>();
+                if wrong.is_empty() {
+                    s.push_str("All questions are correct!\n");
                 } else {
-                    let mut c = answer_counter();
-                    (
-                        i + 1,
-                        (
-                            q.answers
-                                .iter()
-                                .filter_map(|x| {
-                                    let answer = c.next().unwrap();
-                                    if x.is_correct { Some(answer) } else { None }
-                                })
-                                .collect(),
-                            false,
-                        ),
-                    )
+                    s.push_str("## Missed questions\n\n");
+                }
+                for q in wrong {
+                    let key = answers.get(*q);
+                    let label = if matches!(key, Some(Key::Essay { .. })) {
+                        "Your scores"
+                    } else {
+                        "Your answer"
+                    };
+                    let response = self.response(name, *q, key);
+                    if let Some(markdown) = answers.question_markdown(*q) {
+                        write!(s, "{markdown}    {label}: {response}\n\n").unwrap();
+                    } else {
+                        write!(s, "{q}. {label}: {response}\n\n").unwrap();
+                    }
                 }
+                (name.clone(), s)
             })
-            .collect();
+            .collect()
+    }
 
-        let markdown = Some(
-            quiz.questions
-                .par_iter()
+    /**
+    Format a student's response to a question for their feedback
+    */
+    fn response(&self, name: &str, question: usize, key: Option<&Key>) -> String {
+        if let Some(Key::Essay { rubric }) = key {
+            let scores = self.rubric.get(name).and_then(|x| x.get(&question));
+            return rubric
+                .iter()
                 .enumerate()
-                .map(|(i, (q, c))| {
-                    if let Some((content, answers)) = c {
-                        let pre = format!("{}. ", i + 1);
-                        let sep = format!("\n\n{}", " ".repeat(pre.len()));
-                        format!(
-                            "{pre}{}\n\n{content}\n{}",
-                            q.content
-                                .iter()
-                                .map(|x| x.replace('\n', &sep[1..]))
-                                .collect::<Vec<_>>()
-                                .join(&sep),
-                            q.answers
-                                .iter()
-                                .enumerate()
-                                .fold(String::new(), |mut s, (i, x)| {
-                                    writeln!(s, "    * {}: **{}**\n", x.content, &answers[i])
-                                        .unwrap();
-                                    s
-                                }),
-                        )
-                    } else {
-                        let mut c = answer_counter();
-                        let n = i + 1;
-                        let pre = format!("{n}. ");
-                        let sep = format!("\n\n{}", " ".repeat(pre.len()));
-                        let ans: HashSet<_> = answers.get(&n).unwrap().0.iter().collect();
-                        format!(
-                            "{pre}{}\n\n{}",
-                            q.content
-                                .iter()
-                                .map(|x| x.replace('\n', &sep[1..]))
-                                .collect::<Vec<_>>()
-                                .join(&sep),
-                            q.answers
-                                .iter()
-                                .map(|x| {
-                                    let letter = c.next().unwrap();
-                                    if ans.contains(&letter) {
-                                        format!("    * [X] **{letter}. {}**\n\n", x.content)
-                                    } else {
-                                        format!("    * [ ] {letter}. {}\n\n", x.content)
-                                    }
-                                })
-                                .collect::<String>(),
-                        )
-                    }
+                .map(|(i, criterion)| {
+                    let score = scores
+                        .and_then(|x| x.get(i))
+                        .map_or(0, |x| (*x).min(criterion.points));
+                    format!(
+                        "{} (**{score}** of {})",
+                        criterion.description, criterion.points
+                    )
                 })
-                .collect::<String>(),
-        );
-
-        Answers { answers, markdown }
+                .collect::<Vec<_>>()
+                .join("; ");
+        }
+        let response = self
+            .students
+            .get(name)
+            .and_then(|x| x.get(&question))
+            .filter(|x| x.iter().any(|x| !x.trim().is_empty()));
+        match (response, key) {
+            (None, _) => String::from("*(none)*"),
+            (Some(response), Some(Key::Choice(..) | Key::Ordering(_))) => {
+                format!("**{}**", Key::letters(response).join(" "))
+            }
+            (Some(response), _) => response
+                .iter()
+                .map(|x| format!("**{x}**"))
+                .collect::<Vec<_>>()
+                .join(" / "),
+        }
     }
 
     /**
-    Load from a JSON file
+    Export the grades as CSV
 
-    # Errors
+    Each row is a student (by name) with their score, percent, grade, and the points earned for
+    each question.
 
-    Returns an error if not able to read the file at the given path and deserialize it from JSON
+    # Panics
+
+    Panics if not able to write the CSV or there are no students
     */
-    pub fn from(path: &Path) -> Result<Answers> {
-        let answers = match serde_json::from_str(&std::fs::read_to_string(path)?) {
-            Ok(answers) => answers,
-            Err(e) => {
-                return Err(anyhow!(format!(
-                    "Could not parse {:?}: {e}",
-                    path.display(),
-                )));
-            }
-        };
-        Ok(Answers {
-            answers,
-            markdown: None,
-        })
+    #[must_use]
+    pub fn csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        let mut header = vec![
+            String::from("Name"),
+            String::from("Score"),
+            String::from("Percent"),
+            String::from("Grade"),
+        ];
+        header.extend((1..=self.questions).map(|q| q.to_string()));
+        writer.write_record(&header).unwrap();
+        for student in self.results() {
+            let mut record = vec![
+                student.name.to_string(),
+                fmt_score(student.score),
+                format!("{:.1}", student.percent),
+                student.grade.to_string(),
+            ];
+            record.extend(
+                student
+                    .questions
+                    .values()
+                    .map(|x| x.map(fmt_score).unwrap_or_default()),
+            );
+            writer.write_record(&record).unwrap();
+        }
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
     }
 
     /**
-    Serialize to a JSON string
+    Export the grades and summary statistics as JSON
 
     # Panics
 
-    Panics if not able to serialize to a JSON string
+    Panics if not able to serialize to a JSON string or there are no students
     */
     #[must_use]
     pub fn json(&self) -> String {
-        serde_json::to_string(&self.answers).unwrap()
+        serde_json::to_string(&GradesExport {
+            description: &self.description,
+            students: self.results(),
+            summary: self.summary(&self.ranked()),
+        })
+        .unwrap()
     }
 
     /**
-    Calculate the total number of points in the quiz
+    Get each student's score, name, and wrong questions, sorted by score descending
     */
-    fn total(&self) -> usize {
-        self.answers.values().map(|x| x.0.len()).sum()
+    fn ranked(&self) -> Vec<(f32, &str, &[usize])> {
+        let mut scores = self
+            .scores
+            .iter()
+            .map(|(name, (score, wrong))| (*score, name.as_str(), wrong.as_slice()))
+            .collect::<Vec<_>>();
+        scores.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scores
     }
 
     /**
-    Return the number of questions on the quiz
+    Get each student's results, sorted by name
     */
-    fn questions(&self) -> usize {
-        self.answers.len()
+    fn results(&self) -> Vec<StudentResult<'_>> {
+        self.scores
+            .iter()
+            .map(|(name, (score, wrong))| {
+                let percent = score / self.total * 100.0;
+                let items = self.items.get(name);
+                StudentResult {
+                    name,
+                    version: self.versions.get(name).map(String::as_str),
+                    score: *score,
+                    percent,
+                    grade: self.grades.grade(percent),
+                    wrong,
+                    questions: (1..=self.questions)
+                        .map(|q| (q, items.and_then(|x| x.get(&q)).map(|x| x.0)))
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /**
+    Calculate the summary statistics
+
+    `scores` is sorted by score descending.
+    */
+    fn summary(&self, scores: &[(f32, &str, &[usize])]) -> Summary {
+        let values = scores.iter().map(|x| x.0).collect::<Vec<_>>();
+        let (low, high, mean, _sum, _count) = stats::summary(&values);
+        let std_dev = stats::std_dev(&values);
+        let (reliability_method, reliability) = self.reliability();
+        let mut grades = self
+            .grades
+            .labels()
+            .map(|x| GradeCount {
+                grade: x.to_string(),
+                count: 0,
+            })
+            .collect::<Vec<_>>();
+        for score in &values {
+            let grade = self.grades.grade(score / self.total * 100.0);
+            if let Some(x) = grades.iter_mut().find(|x| x.grade == grade) {
+                x.count += 1;
+            }
+        }
+        Summary {
+            students: self.students.len(),
+            questions: self.questions,
+            total: self.total,
+            high,
+            low,
+            mean,
+            median: stats::quantile(&values, 0.5),
+            first_quartile: stats::quantile(&values, 0.25),
+            third_quartile: stats::quantile(&values, 0.75),
+            std_dev,
+            reliability,
+            reliability_method,
+            sem: reliability.map(|x| stats::sem(std_dev, x)),
+            grades,
+        }
+    }
+
+    /**
+    Calculate the reliability of the scores from the matrix of each student's points earned for
+    each question
+
+    Returns the method (KR-20 if every question was scored either right or wrong, otherwise
+    Cronbach's alpha) and the reliability, if defined.
+    Unanswered questions count as zero points.
+    */
+    fn reliability(&self) -> (&'static str, Option<f32>) {
+        let questions = self
+            .items
+            .values()
+            .flat_map(|x| x.iter().map(|(q, (_earned, points))| (*q, *points)))
+            .collect::<BTreeMap<_, _>>();
+        let matrix = self
+            .items
+            .values()
+            .map(|items| {
+                questions
+                    .keys()
+                    .map(|q| items.get(q).map_or(0.0, |(earned, _points)| *earned))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let dichotomous = matrix.iter().all(|row| {
+            row.iter()
+                .zip(questions.values())
+                .all(|(earned, points)| *earned <= 0.0 || earned >= points)
+        });
+        (
+            if dichotomous {
+                "KR-20"
+            } else {
+                "Cronbach's alpha"
+            },
+            stats::alpha(&matrix),
+        )
+    }
+
+    /**
+    Generate the item analysis markdown
+
+    `scores` is sorted by score descending.
+    Difficulty is the mean fraction of each question's points earned (the proportion correct for
+    questions scored right or wrong), discrimination is the difficulty for the top 27% of students
+    minus the difficulty for the bottom 27%, and the point-biserial is the correlation between the
+    fraction earned and the score.
+    */
+    fn item_analysis(&self, scores: &[(f32, &str, &[usize])]) -> String {
+        // Top and bottom 27% of students (rounded, at least one)
+        let group = ((scores.len() * 27 + 50) / 100).max(1);
+        let difficulty = |students: &[(f32, &str, &[usize])], q: usize| {
+            let v = students
+                .iter()
+                .filter_map(|(_score, name, _wrong)| {
+                    let (earned, points) = self.items.get(*name)?.get(&q)?;
+                    Some(earned / points)
+                })
+                .collect::<Vec<_>>();
+            if v.is_empty() {
+                None
+            } else {
+                Some(v.iter().sum::<f32>() / f32::value_from(v.len()).unwrap())
+            }
+        };
+
+        let questions = self
+            .items
+            .values()
+            .flat_map(BTreeMap::keys)
+            .copied()
+            .collect::<BTreeSet<_>>();
+        let mut items =
+            Veg::table("Question|Difficulty|Discrimination|Point-biserial\n-:|-:|-:|-:");
+        for q in questions {
+            let (earned, totals): (Vec<_>, Vec<_>) = scores
+                .iter()
+                .filter_map(|(score, name, _wrong)| {
+                    let (earned, points) = self.items.get(*name)?.get(&q)?;
+                    Some((earned / points, *score))
+                })
+                .unzip();
+            let upper = difficulty(&scores[..group.min(scores.len())], q);
+            let lower = difficulty(&scores[scores.len().saturating_sub(group)..], q);
+            items.push(Box::new(Item {
+                question: q,
+                difficulty: difficulty(scores, q),
+                discrimination: upper.zip(lower).map(|(u, l)| u - l),
+                correlation: stats::correlation(&earned, &totals),
+            }));
+        }
+
+        let mut s = format!("## Item analysis\n\n{}", items.markdown().unwrap());
+        if !self.choices.is_empty() {
+            let mut distractors = Veg::table("Question|Key|Responses\n-:|-|-");
+            for (q, (letters, options)) in &self.choices {
+                distractors.push(Box::new(self.distractor(*q, letters, *options)));
+            }
+            write!(s, "\n## Distractors\n\n{}", distractors.markdown().unwrap()).unwrap();
+        }
+        s
+    }
+
+    /**
+    Count the students that selected each choice of a choice question
+    */
+    fn distractor(
+        &self,
+        question: usize,
+        letters: &[String],
+        options: Option<usize>,
+    ) -> Distractor {
+        let mut counts = BTreeMap::new();
+        if let Some(options) = options {
+            let mut c = answer_counter();
+            for _ in 0..options {
+                counts.insert(c.next().unwrap(), 0);
+            }
+        }
+        let mut blank = 0;
+        for quiz in self.students.values() {
+            match quiz.get(&question) {
+                Some(response) if !response.is_empty() => {
+                    for letter in Key::letters(response) {
+                        *counts.entry(letter).or_insert(0) += 1;
+                    }
+                }
+                _ => blank += 1,
+            }
+        }
+        let mut responses = counts
+            .iter()
+            .map(|(letter, n)| format!("{letter}: {n}"))
+            .collect::<Vec<_>>();
+        if blank > 0 {
+            responses.push(format!("Blank: {blank}"));
+        }
+        Distractor {
+            question,
+            key: letters.join(", "),
+            responses: responses.join(", "),
+        }
     }
+}
 
-    /**
-    Get the answers for a particular problem
-    */
-    fn get(&self, key: usize) -> Option<&(Vec<String>, bool)> {
-        self.answers.get(&key)
-    }
+//--------------------------------------------------------------------------------------------------
 
-    /**
-    Return the Markdown content
-    */
-    #[must_use]
-    pub fn markdown(&self) -> &Option<String> {
-        &self.markdown
-    }
+/**
+Grades export file format ([`Class::json()`])
+*/
+#[derive(Serialize)]
+struct GradesExport<'a> {
+    description: &'a str,
+    students: Vec<StudentResult<'a>>,
+    summary: Summary,
 }
 
 //--------------------------------------------------------------------------------------------------
 
 /**
-Completed quiz
+Individual student's results in a grades export ([`Class::csv()`], [`Class::json()`])
 */
-#[derive(Deserialize)]
-pub struct Class {
-    description: String,
-    students: BTreeMap<String, BTreeMap<usize, Vec<String>>>,
+#[derive(Serialize)]
+struct StudentResult<'a> {
+    name: &'a str,
 
-    #[serde(skip)]
-    total: usize,
-
-    #[serde(skip)]
-    questions: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<&'a str>,
 
-    #[serde(skip)]
-    scores: BTreeMap<String, (usize, Vec<usize>)>,
+    score: f32,
+    percent: f32,
+    grade: &'a str,
+    wrong: &'a [usize],
+    questions: BTreeMap<usize, Option<f32>>,
 }
 
-impl Class {
-    /**
-    Load from a JSON file
+//--------------------------------------------------------------------------------------------------
 
-    # Errors
+/**
+Summary statistics in a grades export ([`Class::json()`])
+*/
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Summary {
+    students: usize,
+    questions: usize,
+    total: f32,
+    high: f32,
+    low: f32,
+    mean: f32,
+    median: f32,
+    first_quartile: f32,
+    third_quartile: f32,
+    std_dev: f32,
+    reliability: Option<f32>,
+    reliability_method: &'static str,
+    sem: Option<f32>,
+    grades: Vec<GradeCount>,
+}
 
-    Returns an error if not able to read the file at the given path and deserialize it from JSON
-    */
-    pub fn from(path: &Path) -> Result<Class> {
-        let class = match serde_json::from_str(&std::fs::read_to_string(path)?) {
-            Ok(answers) => answers,
-            Err(e) => {
-                return Err(anyhow!(format!(
-                    "Could not parse {:?}: {e}",
-                    path.display(),
-                )));
-            }
-        };
-        Ok(class)
-    }
+/**
+Number of students with a grade
+*/
+#[derive(Serialize)]
+struct GradeCount {
+    grade: String,
+    count: usize,
+}
 
-    /**
-    Compute the scores
+//--------------------------------------------------------------------------------------------------
 
-    # Panics
+/**
+Individual question in an item analysis table
+*/
+struct Item {
+    question: usize,
+    difficulty: Option<f32>,
+    discrimination: Option<f32>,
+    correlation: Option<f32>,
+}
 
-    Panics if not able to resolve the question answers
+impl veg::Table for Item {
+    /**
+    Generate the table row
     */
-    pub fn grade(&mut self, answers: &Answers) {
-        self.total = answers.total();
-        self.questions = answers.questions();
-        self.scores = BTreeMap::new();
-        for (name, quiz) in &self.students {
-            let mut missed = 0;
-            let mut wrong = BTreeSet::new();
-            for (q, a) in quiz {
-                let correct = answers.get(*q).unwrap();
-                if correct.1 {
-                    // Match
-                    for (i, x) in a.iter().enumerate() {
-                        let mut m = 0;
-                        if *x != correct.0[i] {
-                            m += 1;
-                        }
-                        if m > 0 {
-                            missed += m;
-                            wrong.insert(*q);
-                        }
-                    }
-                } else if correct.0.len() == 1 {
-                    // Single answer
-                    if *a != correct.0 {
-                        missed += 1;
-                        wrong.insert(*q);
-                    }
-                } else {
-                    // Multiple answer
-
-                    // Count missing correct answers
-                    for answer in a {
-                        if !correct.0.contains(answer) {
-                            missed += 1;
-                            wrong.insert(*q);
-                        }
-                    }
-
-                    // Count wrong answers
-                    for answer in &correct.0 {
-                        if !a.contains(answer) {
-                            missed += 1;
-                            wrong.insert(*q);
-                        }
-                    }
-                }
-            }
-            self.scores.insert(
-                name.clone(),
-                (self.total - missed, wrong.into_iter().collect()),
-            );
-        }
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.question.to_string(),
+            fmt_stat(self.difficulty),
+            fmt_stat(self.discrimination),
+            fmt_stat(self.correlation),
+        ]
     }
+}
 
-    /**
-    Generate the grade report markdown
+//--------------------------------------------------------------------------------------------------
 
-    # Panics
+/**
+Individual choice question in a distractor table
+*/
+struct Distractor {
+    question: usize,
+    key: String,
+    responses: String,
+}
 
-    Panics if not able to resolve the wrong answers
+impl veg::Table for Distractor {
+    /**
+    Generate the table row
     */
-    #[must_use]
-    pub fn markdown(&self) -> String {
-        let mut scores: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
-        let mut scores_sum = 0;
-        let mut wrongs: BTreeMap<&str, &[usize]> = BTreeMap::new();
-        for (name, (score, wrong)) in &self.scores {
-            wrongs.insert(name, wrong);
-            if let Some(students) = scores.get_mut(score) {
-                students.push(name);
-            } else {
-                scores.insert(*score, vec![name]);
-            }
-            scores_sum += score;
-        }
-
-        let mut pcts = vec![];
-        let mut grades_hist = LETTER_GRADES
-            .iter()
-            .map(|(_, c)| (*c, 0))
-            .collect::<BTreeMap<_, _>>();
-        let mut grades = Veg::table("Name|Score|Percent|Grade|Questions\n-|-:|-:|-|-");
-        for (score, students) in scores.iter().rev() {
-            let pct =
-                f32::value_from(*score).unwrap() / f32::value_from(self.total).unwrap() * 100.0;
-            let grade = letter_grade(pct);
-            for name in students {
-                grades.push(Box::new(Grade::new(
-                    name,
-                    *score,
-                    pct,
-                    grade,
-                    wrongs.get(name).unwrap(),
-                )));
-                pcts.push(pct);
-                grades_hist.entry(grade).and_modify(|x| *x += 1);
-            }
-        }
-
-        let mut stats = Veg::table("Description|Value|Percent|Grade\n-|-:|-:|-");
-        let scores_keys = scores.keys().collect::<Vec<_>>();
-        let (min_pct, max_pct, _mean_pct, _sum, count) = calc_stats(&pcts);
-        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-        let mean_score =
-            (f32::value_from(scores_sum).unwrap() / f32::value_from(count).unwrap() + 0.5) as usize;
-        let mean_pct =
-            f32::value_from(mean_score).unwrap() / f32::value_from(self.total).unwrap() * 100.0;
-        let n_students = self.students.len();
-        for (description, value, percent, grade) in [
-            ("Number of students", n_students, None, None),
-            ("Number of questions", self.questions, None, None),
-            ("Total points", self.total, None, None),
-            (
-                "High score",
-                **scores_keys.last().unwrap(),
-                Some(max_pct),
-                Some(letter_grade(max_pct)),
-            ),
-            (
-                "Low score",
-                **scores_keys.first().unwrap(),
-                Some(min_pct),
-                Some(letter_grade(min_pct)),
-            ),
-            (
-                "Mean score",
-                mean_score,
-                Some(mean_pct),
-                Some(letter_grade(mean_pct)),
-            ),
-        ] {
-            stats.push(Box::new(Stat::new(description, value, percent, grade)));
-        }
-        for (letter, count) in &grades_hist {
-            stats.push(Box::new(Stat::new(
-                &letter.to_string(),
-                *count,
-                Some(
-                    f32::value_from(*count).unwrap() / f32::value_from(n_students).unwrap() * 100.0,
-                ),
-                None,
-            )));
-        }
-
-        format!(
-            "# {}\n\n{}\n{}",
-            self.description,
-            grades.markdown().unwrap(),
-            stats.markdown().unwrap(),
-        )
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.question.to_string(),
+            self.key.clone(),
+            self.responses.clone(),
+        ]
     }
 }
 
@@ -802,9 +4592,9 @@ Individual grade in a grades table
 */
 struct Grade {
     name: String,
-    score: usize,
+    score: f32,
     pct: f32,
-    letter: char,
+    letter: String,
     wrong: Vec<usize>,
 }
 
@@ -812,12 +4602,12 @@ impl Grade {
     /**
     Create an individual grade
     */
-    fn new(name: &str, score: usize, pct: f32, letter: char, wrong: &[usize]) -> Grade {
+    fn new(name: &str, score: f32, pct: f32, letter: &str, wrong: &[usize]) -> Grade {
         Grade {
             name: name.to_string(),
             score,
             pct,
-            letter,
+            letter: letter.to_string(),
             wrong: wrong.to_vec(),
         }
     }
@@ -830,9 +4620,9 @@ impl veg::Table for Grade {
     fn row(&self) -> Vec<String> {
         vec![
             self.name.clone(),
-            self.score.to_string(),
+            fmt_score(self.score),
             fmt_percent(self.pct),
-            self.letter.to_string(),
+            self.letter.clone(),
             self.wrong
                 .iter()
                 .map(ToString::to_string)
@@ -849,21 +4639,21 @@ Individual statistic in the summary table ([`Stats`])
 */
 struct Stat {
     description: String,
-    value: usize,
+    value: Option<f32>,
     pct: Option<f32>,
-    grade: Option<char>,
+    grade: Option<String>,
 }
 
 impl Stat {
     /**
     Create a new statistic
     */
-    fn new(description: &str, value: usize, pct: Option<f32>, grade: Option<char>) -> Stat {
+    fn new(description: &str, value: Option<f32>, pct: Option<f32>, grade: Option<&str>) -> Stat {
         Stat {
             description: description.to_string(),
             value,
             pct,
-            grade,
+            grade: grade.map(String::from),
         }
     }
 }
@@ -875,17 +4665,441 @@ impl veg::Table for Stat {
     fn row(&self) -> Vec<String> {
         vec![
             self.description.clone(),
-            self.value.to_string(),
+            self.value.map(fmt_score).unwrap_or_default(),
             if let Some(pct) = &self.pct {
                 fmt_percent(*pct)
             } else {
                 String::new()
             },
-            if let Some(grade) = &self.grade {
-                grade.to_string()
-            } else {
-                String::new()
-            },
+            self.grade.clone().unwrap_or_default(),
         ]
     }
 }
+
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letters(x: &[&str]) -> Vec<String> {
+        x.iter().map(ToString::to_string).collect()
+    }
+
+    fn choice(key: &[&str], response: &[&str], scoring: Scoring, options: Option<usize>) -> f32 {
+        Key::score_choice(&letters(key), &letters(response), scoring, options, 6.0)
+    }
+
+    fn matched(response: &[&str], scoring: Scoring) -> f32 {
+        Key::score_match(
+            &letters(&["D", "C", "A", "B"]),
+            &letters(response),
+            scoring,
+            4.0,
+        )
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{a} != {b}");
+    }
+
+    #[test]
+    fn score_choice_all_or_nothing() {
+        let s = Scoring::AllOrNothing;
+        assert_close(choice(&["A", "C"], &["A", "C"], s, Some(5)), 6.0);
+        assert_close(choice(&["A", "C"], &["A"], s, Some(5)), 0.0);
+        assert_close(choice(&["A", "C"], &["A", "C", "D"], s, Some(5)), 0.0);
+    }
+
+    #[test]
+    fn score_choice_per_option() {
+        let s = Scoring::PerOption;
+        assert_close(choice(&["B"], &["B"], s, Some(4)), 6.0);
+        assert_close(choice(&["B"], &["C"], s, Some(4)), 0.0);
+        assert_close(choice(&["A", "C"], &["A"], s, Some(5)), 3.0);
+        assert_close(choice(&["A", "C"], &["A", "C", "D"], s, Some(5)), 3.0);
+        assert_close(choice(&["A", "C"], &["A", "D"], s, Some(5)), 0.0);
+    }
+
+    #[test]
+    fn score_choice_right_minus_wrong() {
+        let s = Scoring::RightMinusWrong;
+        assert_close(choice(&["A", "C"], &["A", "C"], s, Some(5)), 6.0);
+        assert_close(choice(&["A", "C"], &["A", "C", "D"], s, Some(5)), 3.0);
+        assert_close(choice(&["A", "C"], &["A", "B", "D"], s, Some(5)), 0.0);
+    }
+
+    #[test]
+    fn score_choice_fractional() {
+        let s = Scoring::Fractional;
+        assert_close(choice(&["A", "C"], &["A", "C"], s, Some(5)), 6.0);
+
+        // 1/2 correct minus 1/3 incorrect
+        assert_close(choice(&["A", "C"], &["A", "D"], s, Some(5)), 1.0);
+
+        // Floor of zero
+        assert_close(choice(&["A", "C"], &["B", "D", "E"], s, Some(5)), 0.0);
+
+        // Without the number of choices, the penalty is per correct answer
+        assert_close(choice(&["A", "C"], &["A", "C", "D"], s, None), 3.0);
+    }
+
+    #[test]
+    fn score_choice_negative_marking() {
+        let s = Scoring::NegativeMarking;
+        assert_close(choice(&["B"], &["B"], s, Some(4)), 6.0);
+        assert_close(choice(&["B"], &[], s, Some(4)), 0.0);
+
+        // Penalty of the points divided by the number of incorrect choices
+        assert_close(choice(&["B"], &["C"], s, Some(4)), -2.0);
+        assert_close(choice(&["B"], &["C", "D"], s, Some(4)), -2.0);
+        assert_close(choice(&["B"], &["C"], s, None), -6.0);
+    }
+
+    #[test]
+    fn score_match_all_or_nothing() {
+        let s = Scoring::AllOrNothing;
+        assert_close(matched(&["D", "C", "A", "B"], s), 4.0);
+        assert_close(matched(&["D", "C", "B", "A"], s), 0.0);
+    }
+
+    #[test]
+    fn score_match_per_option() {
+        let s = Scoring::PerOption;
+        assert_close(matched(&["D", "C", "A", "B"], s), 4.0);
+        assert_close(matched(&["D", "C", "B", "A"], s), 2.0);
+        assert_close(matched(&["D", "C"], s), 2.0);
+        assert_close(matched(&[], s), 0.0);
+    }
+
+    fn parse(input: &str) -> (Vec<Question>, Vec<ParseError>) {
+        Bank::parse(input, Path::new("bank.md"))
+    }
+
+    fn errors(errors: &[ParseError]) -> Vec<(usize, usize, &str)> {
+        errors
+            .iter()
+            .map(|x| (x.line(), x.column(), x.message()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_errors() {
+        let (questions, e) = parse(
+            "What is 1 + 1?\n\n---\n\n\
+            Pick one.\n\n* **A**\n* \n\n---\n\n\
+            Match each.\n\n- Cat => Gato\n- Dog Perro\n\n---\n\n\n---\n\n\
+            Good?\n\n* **Yes**\n* No\n",
+        );
+        assert_eq!(questions.len(), 1);
+        assert_eq!(questions[0].line(), 22);
+        assert_eq!(
+            errors(&e),
+            [
+                (
+                    1,
+                    1,
+                    "Missing answers (the last block of a question must be a list)",
+                ),
+                (8, 1, "Empty answer"),
+                (15, 1, "Match item is missing ` => ` (use `* ` for choices)"),
+                (20, 1, "Empty question"),
+            ],
+        );
+    }
+
+    #[test]
+    fn parse_metadata() {
+        let (questions, e) = parse(
+            "<!--\nid: sum\ntags: arithmetic, addition\npoints: 5\ndifficulty: 2\n-->\n\
+            What is 1 + 1?\n\n* **2**\n* 3\n\n---\n\n\
+            <!-- TODO: reword this -->\nWhat is 2 + 2?\n\n* **4**\n* 5\n",
+        );
+        assert!(e.is_empty());
+        let meta = questions[0].meta();
+        assert_eq!(meta.id(), Some("sum"));
+        assert_eq!(meta.tags(), ["arithmetic", "addition"]);
+        assert_eq!((meta.points, meta.difficulty), (Some(5.0), Some(2)));
+        assert_eq!(questions[0].content.len(), 1);
+        assert!(questions[1].meta().id().is_none());
+        assert_eq!(
+            questions[1].content,
+            ["<!-- TODO: reword this -->", "What is 2 + 2?"],
+        );
+    }
+
+    #[test]
+    fn parse_metadata_errors() {
+        let (questions, e) = parse(
+            "<!--\npoints: 5\ndificulty: 2\n-->\nWhat is 1 + 1?\n\n* **2**\n* 3\n\n---\n\n\
+            <!--\npoints: 2\nfix this later\n-->\nWhat is 3 + 3?\n\n* **6**\n* 7\n",
+        );
+        assert!(questions.is_empty());
+        assert_eq!(
+            errors(&e),
+            [
+                (1, 1, "Unknown metadata key: `dificulty`"),
+                (12, 1, "Invalid metadata line: `fix this later`"),
+            ],
+        );
+    }
+
+    fn example() -> Bank {
+        Bank::new(&[PathBuf::from("example/src")]).unwrap()
+    }
+
+    /// Answer key JSON without the generation time
+    fn key_json(answers: &Answers) -> serde_json::Value {
+        let mut json: serde_json::Value = serde_json::from_str(&answers.json()).unwrap();
+        json.as_object_mut().unwrap().remove("generated");
+        json
+    }
+
+    #[test]
+    fn seeded_quizzes_are_reproducible() {
+        let bank = example();
+        let quiz = |seed| bank.quiz(&QuizOptions::default().seed(Some(seed))).unwrap();
+        let (a, b, c) = (quiz(1_234_567_890), quiz(1_234_567_890), quiz(1));
+        assert_eq!(a.seed(), 1_234_567_890);
+        assert_eq!(a.answers().seed(), Some(1_234_567_890));
+        assert_eq!(a.markdown(), b.markdown());
+        assert_eq!(a.answers().markdown(), b.answers().markdown());
+        assert_eq!(key_json(&a.answers()), key_json(&b.answers()));
+        assert_ne!(a.markdown(), c.markdown());
+        assert_eq!(
+            a.markdown(),
+            std::fs::read_to_string("example/1/quiz.md").unwrap(),
+        );
+
+        // A single version is the same quiz
+        let versions = bank
+            .quizzes(1, &QuizOptions::default().seed(Some(1_234_567_890)))
+            .unwrap();
+        assert_eq!(versions[0].markdown(), a.markdown());
+    }
+
+    #[test]
+    fn quotas() {
+        let bank = example();
+        let quiz = |count, quotas: &[(&str, usize)]| {
+            let quotas = quotas
+                .iter()
+                .map(|(path, n)| (PathBuf::from(path), *n))
+                .collect::<Vec<_>>();
+            bank.quiz(
+                &QuizOptions::default()
+                    .seed(Some(1))
+                    .count(count)
+                    .quotas(&quotas),
+            )
+        };
+        let from = |quiz: &Quiz, path: &str| {
+            quiz.questions
+                .iter()
+                .filter(|(q, _)| q.source().ends_with(path))
+                .count()
+        };
+
+        assert_eq!(quiz(None, &[]).unwrap().questions.len(), 21);
+        let x = quiz(Some(6), &[("addition.md", 2), ("match.md", 1)]).unwrap();
+        assert_eq!(x.questions.len(), 6);
+        assert_eq!((from(&x, "addition.md"), from(&x, "match.md")), (2, 1));
+        let x = quiz(None, &[("addition.md", 1)]).unwrap();
+        assert_eq!((x.questions.len(), from(&x, "addition.md")), (20, 1));
+
+        let error = |count, quotas| quiz(count, quotas).unwrap_err().to_string();
+        assert_eq!(
+            error(Some(6), &[("addition.md", 3)]),
+            "Not enough questions for quotas: `addition.md` (2 of 3)!",
+        );
+        assert_eq!(
+            error(Some(2), &[("addition.md", 2), ("match.md", 1)]),
+            "Quotas (3) exceed the number of questions (2)!",
+        );
+        assert_eq!(error(Some(30), &[]), "Not enough questions: 21 of 30!");
+    }
+
+    fn csv(name: &str, content: &str, columns: &str) -> Result<Class> {
+        let path = std::env::temp_dir().join(format!("quixote-test-{name}.csv"));
+        std::fs::write(&path, content).unwrap();
+        let columns = if columns.is_empty() {
+            Columns::default()
+        } else {
+            columns.parse().unwrap()
+        };
+        let class = Class::from_csv(&path, &columns);
+        std::fs::remove_file(&path).unwrap();
+        class
+    }
+
+    fn responses(class: &Class, name: &str) -> Vec<(usize, Vec<String>)> {
+        class.students[name]
+            .iter()
+            .map(|(q, x)| (*q, x.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn csv_columns() {
+        let class = csv(
+            "detect",
+            "Timestamp 2025,Name,Version,Q1,Question 2,3\n\
+            t,Ann,B,A;C,B,dog\n\
+            t,Bob,,AC,,\n",
+            "",
+        );
+        let class = class.unwrap();
+        assert_eq!(
+            responses(&class, "Ann"),
+            [
+                (1, letters(&["A", "C"])),
+                (2, letters(&["B"])),
+                (3, letters(&["dog"])),
+            ],
+        );
+        assert_eq!(responses(&class, "Bob"), [(1, letters(&["AC"]))]);
+        assert_eq!(class.versions.get("Ann").map(String::as_str), Some("B"));
+        assert!(!class.versions.contains_key("Bob"));
+
+        let class = csv(
+            "map",
+            "Student,Form,Pick,Word,4\nAnn,A,C,cat,D\n",
+            "name=Student,version=Form,1=Pick,2=Word",
+        )
+        .unwrap();
+        assert_eq!(
+            responses(&class, "Ann"),
+            [(1, letters(&["C"])), (2, letters(&["cat"]))],
+        );
+        assert_eq!(class.versions["Ann"], "A");
+    }
+
+    #[test]
+    fn csv_errors() {
+        let error = |name, content, columns| {
+            let e = csv(name, content, columns).err().unwrap().to_string();
+            e.rsplit(": ").next().unwrap().to_string()
+        };
+        assert_eq!(
+            error("duplicate-column", "Name,Q1,1\nAnn,A,B\n", ""),
+            "Columns `Q1` and `1` are both question 1",
+        );
+        assert_eq!(
+            error("missing-column", "Name,1\nAnn,A\n", "name=Who"),
+            "Missing column `Who`",
+        );
+        assert_eq!(
+            error("duplicate-student", "Name,1\nAnn,A\nAnn,B\n", ""),
+            "Duplicate student `Ann` in row 3",
+        );
+        assert_eq!(
+            error("missing-name", "Name,1\nAnn,A\n,B\n", ""),
+            "Missing student name in row 3",
+        );
+        assert!(
+            csv("empty", "Name,1\n", "")
+                .err()
+                .unwrap()
+                .to_string()
+                .starts_with("No students in ")
+        );
+
+        assert_eq!(
+            "1=A,1=B".parse::<Columns>().unwrap_err(),
+            "Duplicate question `1`",
+        );
+        assert_eq!(
+            "who=A".parse::<Columns>().unwrap_err(),
+            "Expected `name`, `version`, or a question number: `who`",
+        );
+    }
+
+    #[test]
+    fn lint_example() {
+        let lints = Bank::lint(&[PathBuf::from("example/src")]).unwrap();
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].path(), Path::new("example/src/multiple-answer.md"));
+        assert_eq!((lints[0].line(), lints[0].column()), (1, 1));
+        assert_eq!(lints[0].severity(), Severity::Warning);
+        assert!(lints[0].message().starts_with("All answers are correct"));
+    }
+
+    fn key(json: &str) -> Key {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn validate_uppercase_text_responses() {
+        let short = key(r#"{"answers":["NASA"],"ignore-case":false,"normalize-whitespace":true}"#);
+        assert!(short.validate(&letters(&["NASA"]), None).is_empty());
+        assert_eq!(
+            short.validate(&letters(&["NASA", "ESA"]), None),
+            ["Expected a single answer but found 2"],
+        );
+
+        let cloze = key(
+            r#"{"blanks":[{"answers":["DNA"],"ignore-case":false,"normalize-whitespace":true}]}"#,
+        );
+        assert!(cloze.validate(&letters(&["DNA"]), None).is_empty());
+        assert_eq!(
+            cloze.validate(&letters(&["DNA", "RNA"]), None),
+            ["Expected at most 1 blanks but found 2"],
+        );
+    }
+
+    #[test]
+    fn validate_letters() {
+        let choice = key(r#"[["A","C"],false]"#);
+        assert!(choice.validate(&letters(&["AC"]), Some(4)).is_empty());
+        assert_eq!(
+            choice.validate(&letters(&["AE"]), Some(4)),
+            ["Letter `E` is out of range (`A` to `D`)"],
+        );
+        assert_eq!(
+            choice.validate(&letters(&["A", "A"]), Some(4)),
+            ["Duplicate letter `A`"],
+        );
+
+        let matched = key(r#"[["D","C","A","B"],true]"#);
+        assert!(matched.validate(&letters(&["DCAA"]), None).is_empty());
+        assert_eq!(
+            matched.validate(&letters(&["DCA"]), None),
+            ["Expected 4 letters (one per item) but found 3"],
+        );
+
+        let ordering = key(r#"{"order":["C","A","B"],"scoring":"per-position"}"#);
+        assert!(ordering.validate(&letters(&["CAB"]), None).is_empty());
+        assert_eq!(
+            ordering.validate(&letters(&["CAA"]), None),
+            ["Duplicate letter `A`"],
+        );
+    }
+
+    #[test]
+    fn numeric_matches() {
+        let x = Numeric::new("3.14 ± 0.01").unwrap();
+        assert!(x.matches("3.14"));
+        assert!(x.matches("3.15"));
+        assert!(x.matches(" 3.13 "));
+        assert!(!x.matches("3.16"));
+        assert!(!x.matches("pi"));
+        assert!(!x.matches("3.14 m"));
+
+        let x = Numeric::new("240 miles").unwrap();
+        assert!(x.matches("240 miles"));
+        assert!(x.matches("240 Miles"));
+        assert!(x.matches("240"));
+        assert!(!x.matches("240 km"));
+        assert!(!x.matches("241 miles"));
+
+        let x = Numeric::new("100 +/- 5%").unwrap();
+        assert!(x.matches("105"));
+        assert!(x.matches("95.5"));
+        assert!(!x.matches("94"));
+
+        // Floating point rounding error
+        let x = Numeric::new("0.3").unwrap();
+        assert!(x.matches("0.30000000000000004"));
+        assert!(!x.matches("0.31"));
+    }
+}
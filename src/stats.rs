@@ -0,0 +1,177 @@
+/*!
+Descriptive statistics and test reliability for grade reports
+*/
+
+use conv::ValueFrom;
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Calculate basic statistics of a non-empty sample
+
+```text
+let (min, max, mean, sum, count) = stats::summary(&[1.0, 2.0, ...]);
+```
+*/
+pub fn summary(v: &[f32]) -> (f32, f32, f32, f32, usize) {
+    let mut min = v[0];
+    let mut max = v[0];
+    let mut sum = v[0];
+    for i in v.iter().skip(1) {
+        min = min.min(*i);
+        max = max.max(*i);
+        sum += i;
+    }
+    let count = v.len();
+    let mean = sum / f32::value_from(count).unwrap();
+    (min, max, mean, sum, count)
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Calculate the mean of a sample
+*/
+pub fn mean(v: &[f32]) -> f32 {
+    v.iter().sum::<f32>() / f32::value_from(v.len()).unwrap()
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Calculate the `p` quantile (`0.0..=1.0`) of a non-empty sample
+
+Interpolates linearly between the closest ranks, so the median of an even sized sample is the mean
+of the middle two values.
+*/
+pub fn quantile(v: &[f32], p: f32) -> f32 {
+    let mut v = v.to_vec();
+    v.sort_by(f32::total_cmp);
+    let rank = p * f32::value_from(v.len() - 1).unwrap();
+    let i = (0..v.len())
+        .rev()
+        .find(|i| f32::value_from(*i).unwrap() <= rank)
+        .unwrap_or(0);
+    let fraction = rank - f32::value_from(i).unwrap();
+    match v.get(i + 1) {
+        Some(next) => v[i] + (next - v[i]) * fraction,
+        None => v[i],
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Calculate the sample variance (zero if there are fewer than 2 values)
+*/
+pub fn variance(v: &[f32]) -> f32 {
+    if v.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(v);
+    v.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / f32::value_from(v.len() - 1).unwrap()
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Calculate the sample standard deviation
+*/
+pub fn std_dev(v: &[f32]) -> f32 {
+    variance(v).sqrt()
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Calculate the Pearson correlation coefficient of two equal length samples
+
+Returns `None` if either sample has no variance.
+*/
+pub fn correlation(x: &[f32], y: &[f32]) -> Option<f32> {
+    let mean_x = mean(x);
+    let mean_y = mean(y);
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x_i, y_i) in x.iter().zip(y) {
+        cov += (x_i - mean_x) * (y_i - mean_y);
+        var_x += (x_i - mean_x).powi(2);
+        var_y += (y_i - mean_y).powi(2);
+    }
+    if var_x > 0.0 && var_y > 0.0 {
+        Some(cov / (var_x * var_y).sqrt())
+    } else {
+        None
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Calculate Cronbach's alpha for a matrix of item scores (one row per student, one column per item)
+
+Equivalent to KR-20 if every item is scored right or wrong.
+Returns `None` if there are fewer than 2 items or the total scores have no variance.
+*/
+pub fn alpha(matrix: &[Vec<f32>]) -> Option<f32> {
+    let k = matrix.first()?.len();
+    if k < 2 {
+        return None;
+    }
+    let totals = matrix.iter().map(|x| x.iter().sum()).collect::<Vec<f32>>();
+    let total_variance = variance(&totals);
+    if total_variance <= 0.0 {
+        return None;
+    }
+    let item_variance = (0..k)
+        .map(|j| variance(&matrix.iter().map(|x| x[j]).collect::<Vec<_>>()))
+        .sum::<f32>();
+    let k = f32::value_from(k).unwrap();
+    Some(k / (k - 1.0) * (1.0 - item_variance / total_variance))
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Calculate the standard error of measurement from the standard deviation of the scores and the
+reliability
+*/
+pub fn sem(std_dev: f32, reliability: f32) -> f32 {
+    std_dev * (1.0 - reliability).max(0.0).sqrt()
+}
+
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{a} != {b}");
+    }
+
+    #[test]
+    fn quantile_interpolates() {
+        let v = [3.0, 1.0, 4.0, 2.0];
+        assert_close(quantile(&v, 0.0), 1.0);
+        assert_close(quantile(&v, 0.25), 1.75);
+        assert_close(quantile(&v, 0.5), 2.5);
+        assert_close(quantile(&v, 0.75), 3.25);
+        assert_close(quantile(&v, 1.0), 4.0);
+        assert_close(quantile(&[1.0, 2.0, 3.0], 0.5), 2.0);
+        assert_close(quantile(&[7.0], 0.5), 7.0);
+    }
+
+    #[test]
+    fn alpha_reliability() {
+        assert_close(alpha(&[vec![1.0, 1.0], vec![0.0, 0.0]]).unwrap(), 1.0);
+        assert_close(
+            alpha(&[vec![1.0, 1.0], vec![0.0, 0.0], vec![1.0, 0.0]]).unwrap(),
+            2.0 / 3.0,
+        );
+
+        // Fewer than 2 items, or no variance
+        assert!(alpha(&[]).is_none());
+        assert!(alpha(&[vec![1.0], vec![0.0]]).is_none());
+        assert!(alpha(&[vec![1.0, 0.0], vec![0.0, 1.0]]).is_none());
+    }
+}